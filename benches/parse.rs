@@ -0,0 +1,70 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pw::{find, parse, Entry, VALID};
+
+const ENTRIES: usize = 100_000;
+const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-_.!";
+
+fn fixture(entries: usize) -> String {
+    let mut seed: u64 = 0x5eed;
+    let mut random = move || {
+        seed = seed
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (seed >> 33) as usize
+    };
+
+    let mut data = String::with_capacity(entries * 96);
+    for i in 0..entries {
+        if i % 50 == 0 {
+            data.push_str(&format!("# group {}\n", i / 50));
+        }
+        let marker = match random() % 20 {
+            0 => "-",
+            1 => "*",
+            _ => "+",
+        };
+        let password: String = (0..20)
+            .map(|_| CHARS[random() % CHARS.len()] as char)
+            .collect();
+        data.push_str(&format!(
+            "{} group{}/account{} https://www.site{}.example.com user{}@example.com {}",
+            marker,
+            i % 100,
+            i,
+            i % 1000,
+            i,
+            password
+        ));
+        if i % 3 == 0 {
+            data.push_str(&format!(
+                " tags=t{},shared changed=2022-01-{:02}",
+                i % 7,
+                i % 28 + 1
+            ));
+        }
+        data.push('\n');
+    }
+    data
+}
+
+fn benches(c: &mut Criterion) {
+    let data = fixture(ENTRIES);
+    let last = format!("group{}/account{}", (ENTRIES - 1) % 100, ENTRIES - 1);
+
+    c.bench_function("parse 100k entries", |b| {
+        b.iter(|| parse(black_box(&data)).filter(Result::is_ok).count())
+    });
+    c.bench_function("valid entries of 100k", |b| {
+        b.iter(|| {
+            parse(black_box(&data))
+                .filter(|entry| matches!(entry, Ok(Entry::Valid(_))))
+                .count()
+        })
+    });
+    c.bench_function("find the last of 100k entries", |b| {
+        b.iter(|| find(black_box(&data), VALID, black_box(&last)).is_ok())
+    });
+}
+
+criterion_group!(parse_benches, benches);
+criterion_main!(parse_benches);
@@ -0,0 +1,67 @@
+use crate::audit;
+use crate::clip;
+use crate::cmdline::Selection;
+use crate::config::Config;
+use crate::err::Error;
+use crate::file;
+use crate::gen::{Generator, Policy};
+use crate::{check_value, parse, read, Secret};
+use log::info;
+use std::path::PathBuf;
+
+pub fn append_entry(data: &str, fields: &[&str]) -> Secret<String> {
+    let line_ending = file::line_ending(data);
+    let mut out = Secret::new(String::with_capacity(data.len() + 128));
+    out.push_str(data);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push_str(line_ending);
+    }
+    out.push('+');
+    for field in fields {
+        out.push(' ');
+        out.push_str(field);
+    }
+    out.push_str(line_ending);
+    out
+}
+
+pub fn add(
+    file: PathBuf,
+    fields: Vec<String>,
+    policy_name: Option<String>,
+    policy: Policy,
+    selection: Selection,
+    generator: &Generator,
+    config: &Config,
+) -> Result<(), Error> {
+    let (name, link, username) = (&fields[0], &fields[1], &fields[2]);
+    check_value("account name", name)?;
+    check_value("link", link)?;
+    check_value("username", username)?;
+    let policy_field = policy_name.map(|name| format!("policy={}", name));
+
+    let data = read(&file)?;
+    for entry in parse(&data) {
+        if entry?.data().named(name) {
+            return Err(Error::Exists(name.to_string()));
+        }
+    }
+
+    let password = generator.generate(&policy)?;
+    let changed = format!("changed={}", file::today());
+    let mut fields = vec![name.as_str(), link, username, password.as_str()];
+    fields.extend(policy_field.as_deref());
+    fields.push(&changed);
+    let edited = append_entry(&data, &fields);
+
+    file::write(&file, &edited)?;
+    audit::record(config, "add", &[name])?;
+
+    if !file::dry_run() {
+        clip::copy(&password, selection)?;
+    }
+
+    info!("Added {} with a new password", name);
+
+    Ok(())
+}
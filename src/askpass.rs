@@ -0,0 +1,52 @@
+use crate::audit;
+use crate::config::Config;
+use crate::err::Error;
+use crate::link;
+use crate::{find_all, read, EntryData, VALID};
+use std::path::{Path, PathBuf};
+
+enum Prompt<'a> {
+    Key(&'a str),
+    Login(&'a str, &'a str),
+}
+
+impl<'a> Prompt<'a> {
+    fn parse(prompt: &'a str) -> Option<Self> {
+        let prompt = prompt.trim();
+        if let Some(key) = prompt.strip_prefix("Enter passphrase for ") {
+            let key = key.strip_prefix("key ").unwrap_or(key);
+            let key = key.split(" (").next().unwrap_or(key);
+            return Some(Prompt::Key(key.trim_end_matches(':').trim_matches('\'')));
+        }
+        let login = prompt.strip_suffix("'s password:")?;
+        let (username, host) = login.split_once('@')?;
+        Some(Prompt::Login(username, host))
+    }
+
+    fn matches(&self, entry: &EntryData) -> bool {
+        match *self {
+            Prompt::Key(key) => expand(entry.link) == Path::new(key),
+            Prompt::Login(username, host) => {
+                entry.username == username && link::host(entry.link).as_deref() == Some(host)
+            }
+        }
+    }
+}
+
+fn expand(link: &str) -> PathBuf {
+    match (link.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(link),
+    }
+}
+
+pub fn askpass(file: PathBuf, prompt: String, config: &Config) -> Result<(), Error> {
+    let query = Prompt::parse(&prompt).ok_or_else(|| Error::NoMatches(prompt.clone()))?;
+
+    let data = read(file)?;
+    let entry = find_all(&data, VALID, &[&prompt], |entry, _| query.matches(entry))?.remove(0);
+    audit::record(config, "askpass", &[entry.name])?;
+    println!("{}", entry.password);
+
+    Ok(())
+}
@@ -0,0 +1,166 @@
+use crate::audit;
+use crate::cmdline::{Attach, Mark};
+use crate::config::Config;
+use crate::err::Error;
+use crate::file;
+use crate::{check_value, find, read, ACTIVE, ANY};
+use log::info;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const GPG: &str = "gpg";
+const MAX_SIZE: u64 = 1024 * 1024;
+
+fn dir(file: &Path, acc: &str) -> PathBuf {
+    file::with_suffix(file, ".attachments").join(acc)
+}
+
+fn account(file: &Path, states: &[Mark], acc: &str) -> Result<String, Error> {
+    let data = read(file)?;
+    let entry = find(&data, states, acc)?;
+    Ok(entry.name.to_string())
+}
+
+fn add(
+    file: &Path,
+    acc: &str,
+    path: &Path,
+    name: Option<String>,
+    config: &Config,
+) -> Result<(), Error> {
+    let key = config.attach_key().ok_or(Error::NoAttachKey)?;
+    let acc = account(file, ACTIVE, acc)?;
+    let name = match name {
+        Some(name) => name,
+        None => path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    check_value("attachment name", &name)?;
+    if name.contains('/') {
+        return Err(Error::InvalidValue("attachment name", name));
+    }
+
+    let size = fs::metadata(path).map_err(Error::AttachRead)?.len();
+    if size > MAX_SIZE {
+        return Err(Error::AttachmentTooLarge(
+            path.display().to_string(),
+            MAX_SIZE,
+        ));
+    }
+
+    let dir = dir(file, &acc);
+    let out = dir.join(format!("{}.asc", name));
+    if file::dry_run() {
+        println!(
+            "Would attach {} to {} at {}",
+            path.display(),
+            acc,
+            out.display()
+        );
+        return Ok(());
+    }
+    fs::create_dir_all(&dir).map_err(Error::AttachWrite)?;
+
+    let exit_status = process::Command::new(GPG)
+        .arg("--batch")
+        .arg("--yes")
+        .arg("--armor")
+        .args(["--recipient", key])
+        .arg("--encrypt")
+        .arg("--output")
+        .arg(&out)
+        .arg(path)
+        .stdin(process::Stdio::null())
+        .status()
+        .map_err(Error::GpgSpawn)?;
+
+    if !exit_status.success() {
+        return Err(Error::AttachErr("encrypt"));
+    }
+    info!("Attached {} to {} as {}", path.display(), acc, name);
+
+    Ok(())
+}
+
+fn get(
+    file: &Path,
+    acc: &str,
+    name: &str,
+    output: Option<PathBuf>,
+    config: &Config,
+) -> Result<(), Error> {
+    let acc = account(file, ANY, acc)?;
+    let attachment = dir(file, &acc).join(format!("{}.asc", name));
+    if !attachment.is_file() {
+        return Err(Error::NoAttachment(acc, name.to_string()));
+    }
+    audit::record(config, "attach", &[&acc])?;
+
+    let mut command = process::Command::new(GPG);
+    command.arg("--batch").arg("--quiet").arg("--yes");
+    if let Some(output) = &output {
+        command.arg("--output").arg(output);
+    }
+    let exit_status = command
+        .arg("--decrypt")
+        .arg(&attachment)
+        .status()
+        .map_err(Error::GpgSpawn)?;
+
+    if !exit_status.success() {
+        return Err(Error::AttachErr("decrypt"));
+    }
+
+    Ok(())
+}
+
+fn list(file: &Path, acc: &str) -> Result<(), Error> {
+    let acc = account(file, ANY, acc)?;
+    let entries = match fs::read_dir(dir(file, &acc)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(Error::AttachRead(e)),
+    };
+
+    let mut names = Vec::new();
+    for entry in entries {
+        let name = entry.map_err(Error::AttachRead)?.file_name();
+        if let Some(name) = name.to_string_lossy().strip_suffix(".asc") {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    for name in names {
+        println!("{}", name);
+    }
+
+    Ok(())
+}
+
+pub fn rename(file: &Path, old: &str, new: &str) -> Result<(), Error> {
+    let old = dir(file, old);
+    if !old.is_dir() || file::dry_run() {
+        return Ok(());
+    }
+    let new = dir(file, new);
+    if let Some(parent) = new.parent() {
+        fs::create_dir_all(parent).map_err(Error::AttachWrite)?;
+    }
+    fs::rename(old, new).map_err(Error::AttachWrite)
+}
+
+pub fn attach(file: PathBuf, command: Attach, config: &Config) -> Result<(), Error> {
+    match command {
+        Attach::Add {
+            acc, path, name, ..
+        } => add(&file, &acc, &path, name, config),
+        Attach::Get {
+            acc, name, output, ..
+        } => get(&file, &acc, &name, output, config),
+        Attach::List { acc, .. } => list(&file, &acc),
+    }
+}
@@ -0,0 +1,21 @@
+use crate::config::Config;
+use crate::err::Error;
+use crate::file;
+use log::debug;
+use std::time::SystemTime;
+
+pub fn record<S: AsRef<str>>(config: &Config, command: &str, accs: &[S]) -> Result<(), Error> {
+    let log = match config.audit_log() {
+        Some(log) => log,
+        None => return Ok(()),
+    };
+
+    let now = humantime::format_rfc3339_seconds(SystemTime::now());
+    for acc in accs {
+        let line = format!("{} {} {}", now, command, acc.as_ref());
+        file::append(log, &line).map_err(Error::AuditWrite)?;
+    }
+    debug!("Recorded {} in the audit log at {}", command, log.display());
+
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use crate::err::Error;
+use crate::file;
+use crate::{location, quiet, visit, Entry, Secret, Visitor};
+use std::char::REPLACEMENT_CHARACTER;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+fn read_lossy(file: PathBuf) -> Result<(Secret<String>, Vec<Error>), Error> {
+    let bytes = file::load(&file)?;
+    let mut data = Secret::new(String::with_capacity(bytes.len() * 3));
+    let mut errors = Vec::new();
+    for chunk in bytes.utf8_chunks() {
+        data.push_str(chunk.valid());
+        if !chunk.invalid().is_empty() {
+            let (line, column) = location(&data);
+            errors.push(Error::InvalidUtf8(line, column));
+            data.push(REPLACEMENT_CHARACTER);
+        }
+    }
+    Ok((data, errors))
+}
+
+fn mask(line: &str) -> String {
+    let line: String = line
+        .chars()
+        .map(|c| match c {
+            '\t' => ' ',
+            c if c.is_control() || c == '\u{feff}' => '?',
+            c => c,
+        })
+        .collect();
+    let mut masked = line.clone();
+    for (i, token) in line.split_whitespace().enumerate().skip(4) {
+        let hidden = match token.split_once('=') {
+            _ if i == 4 => "*".repeat(token.chars().count()),
+            Some((key, value)) if secret_field(key) => {
+                format!("{}={}", key, "*".repeat(value.chars().count()))
+            }
+            _ => continue,
+        };
+        masked = file::replace_token(&masked, i, &hidden);
+    }
+    masked
+}
+
+pub fn secret_field(key: &str) -> bool {
+    key == "recovery"
+        || key
+            .strip_prefix("otp")
+            .is_some_and(|n| n.is_empty() || n.parse::<u32>().is_ok())
+        || key
+            .strip_prefix('a')
+            .is_some_and(|question| question.parse::<u32>().is_ok())
+}
+
+fn diagnostic(data: &str, e: &Error) -> String {
+    let (num, column) = match e.location() {
+        Some(location) => location,
+        None => return e.to_string(),
+    };
+    let line = data.lines().nth(num - 1).unwrap_or_default();
+    format!("{}\n  {}\n  {:>3$}", e, mask(line), "^", column)
+}
+
+fn whitespace(num: usize, line: &str, warnings: &mut Vec<(usize, String)>) {
+    let trimmed = line.trim();
+    if trimmed.is_empty() || trimmed.starts_with('#') {
+        return;
+    }
+    if trimmed != line {
+        warnings.push((num, "leading or trailing whitespace".to_string()));
+    }
+    if trimmed.contains('\t') || trimmed.contains("  ") {
+        warnings.push((num, "irregular spacing between fields".to_string()));
+    }
+}
+
+#[derive(Default)]
+pub struct Checker<'a> {
+    errors: Vec<Error>,
+    warnings: Vec<(usize, String)>,
+    valid: usize,
+    invalid: usize,
+    change: usize,
+    names: HashMap<&'a str, (usize, &'a str)>,
+    valid_names: HashMap<&'a str, usize>,
+    rows: Vec<(usize, String, String)>,
+}
+
+impl<'a> Visitor<'a> for Checker<'a> {
+    fn line(&mut self, num: usize, line: &'a str) {
+        whitespace(num, line.trim_end_matches('\r'), &mut self.warnings);
+    }
+
+    fn entry(&mut self, entry: Entry<'a>) -> Result<(), Error> {
+        match entry {
+            Entry::Valid(_) => self.valid += 1,
+            Entry::Invalid(_) => self.invalid += 1,
+            Entry::Change(_) => self.change += 1,
+        }
+        let data = entry.data();
+        self.rows
+            .push((data.line, data.marker.to_string(), data.name.to_string()));
+        let keys: Vec<&str> = Some(data.name).into_iter().chain(data.aliases()).collect();
+        if let Entry::Valid(_) = entry {
+            let mut duplicate = false;
+            for key in &keys {
+                match self.valid_names.insert(key, data.line) {
+                    Some(first) if first != data.line => {
+                        self.errors
+                            .push(Error::Duplicate(data.line, key.to_string(), first));
+                        duplicate = true;
+                    }
+                    _ => {}
+                }
+            }
+            if duplicate {
+                return Ok(());
+            }
+        }
+        for key in &keys {
+            match self.names.insert(key, (data.line, data.username)) {
+                Some((first, username)) if first != data.line => {
+                    let same = if username == data.username {
+                        " with the same username"
+                    } else {
+                        ""
+                    };
+                    self.warnings.push((
+                        data.line,
+                        format!("duplicate name {}{} (also on line {})", key, same, first),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn error(&mut self, e: Error) -> Result<(), Error> {
+        self.errors.push(e);
+        Ok(())
+    }
+}
+
+impl Checker<'_> {
+    pub fn finish(mut self, data: &str, strict: bool, verbose: bool) -> Result<(), Error> {
+        self.errors.sort_by_key(Error::location);
+        let diagnostics: Secret<Vec<String>> =
+            Secret::new(self.errors.iter().map(|e| diagnostic(data, e)).collect());
+
+        self.warnings.sort_by_key(|(num, _)| *num);
+        for (num, warning) in &self.warnings {
+            if quiet() || verbose && self.rows.iter().any(|(line, _, _)| line == num) {
+                continue;
+            }
+            eprintln!("Warning at line {}, {}", num, warning);
+        }
+        for diagnostic in diagnostics.iter() {
+            eprintln!("{}", diagnostic);
+        }
+
+        if !self.errors.is_empty() {
+            return Err(Error::InvalidEntries(self.errors.len()));
+        }
+        if strict && !self.warnings.is_empty() {
+            return Err(Error::Warnings(self.warnings.len()));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn check(file: PathBuf, strict: bool, verbose: bool) -> Result<(), Error> {
+    let (data, errors) = read_lossy(file)?;
+    let mut checker = Checker {
+        errors,
+        ..Checker::default()
+    };
+    visit(&data, &mut checker)?;
+
+    checker.warnings.sort_by_key(|(num, _)| *num);
+    if verbose {
+        for (num, marker, name) in &checker.rows {
+            println!("{:>5}  {} {}", num, marker, name);
+            for (_, warning) in checker.warnings.iter().filter(|(line, _)| line == num) {
+                println!("         warning: {}", warning);
+            }
+        }
+        println!();
+    }
+
+    if !quiet() {
+        println!(
+            "{} current, {} inactive, {} need changing",
+            checker.valid, checker.invalid, checker.change
+        );
+    }
+    checker.finish(&data, strict, verbose)
+}
@@ -0,0 +1,154 @@
+use crate::cmdline::Selection;
+use crate::err::Error;
+use log::{info, warn};
+use std::env;
+use std::io::Write;
+use std::process;
+use std::sync::Mutex;
+
+const CLEAR_AFTER_SECS: u32 = 45;
+
+static UNCLEARED: Mutex<Option<&'static Backend>> = Mutex::new(None);
+
+struct Backend {
+    program: &'static str,
+    args: &'static [&'static str],
+    clear: &'static str,
+}
+
+const WAYLAND: Backend = Backend {
+    program: "wl-copy",
+    args: &[],
+    clear: "wl-copy --clear",
+};
+
+const X11: Backend = Backend {
+    program: "xclip",
+    args: &["-selection", "clipboard", "-in"],
+    clear: "xclip -selection clipboard -in < /dev/null",
+};
+
+const WAYLAND_PRIMARY: Backend = Backend {
+    program: "wl-copy",
+    args: &["--primary"],
+    clear: "wl-copy --primary --clear",
+};
+
+const X11_PRIMARY: Backend = Backend {
+    program: "xclip",
+    args: &["-selection", "primary", "-in"],
+    clear: "xclip -selection primary -in < /dev/null",
+};
+
+const TMUX: Backend = Backend {
+    program: "tmux",
+    args: &["load-buffer", "-b", "pw", "-"],
+    clear: "tmux delete-buffer -b pw",
+};
+
+const MACOS: Backend = Backend {
+    program: "pbcopy",
+    args: &[],
+    clear: "pbcopy < /dev/null",
+};
+
+fn backend(selection: Selection) -> &'static Backend {
+    let wayland = env::var_os("WAYLAND_DISPLAY").is_some();
+    match selection {
+        Selection::Tmux => &TMUX,
+        _ if cfg!(target_os = "macos") => {
+            if selection == Selection::Primary {
+                warn!("There is no primary selection on macOS, using the clipboard");
+            }
+            &MACOS
+        }
+        Selection::Clipboard if wayland => &WAYLAND,
+        Selection::Clipboard => &X11,
+        Selection::Primary if wayland => &WAYLAND_PRIMARY,
+        Selection::Primary => &X11_PRIMARY,
+    }
+}
+
+fn name(selection: Selection) -> &'static str {
+    match selection {
+        Selection::Clipboard => "clipboard",
+        Selection::Primary => "primary selection",
+        Selection::Tmux => "tmux buffer",
+    }
+}
+
+fn copied(selection: Selection) {
+    info!(
+        "Copied to {}, clearing in {} seconds",
+        name(selection),
+        CLEAR_AFTER_SECS
+    );
+}
+
+pub fn copy(text: &str, selection: Selection) -> Result<(), Error> {
+    let backend = backend(selection);
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if backend.program == WAYLAND.program {
+        match crate::wayland::copy(text, selection == Selection::Primary) {
+            Ok(()) => {
+                copied(selection);
+                return Ok(());
+            }
+            Err(e) => warn!("{}, falling back to {}", e, backend.program),
+        }
+    }
+
+    *UNCLEARED.lock().unwrap_or_else(|e| e.into_inner()) = Some(backend);
+
+    let mut child = process::Command::new(backend.program)
+        .args(backend.args)
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .spawn()
+        .map_err(|e| Error::ClipSpawn(backend.program, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).map_err(Error::ClipWrite)?;
+    }
+
+    let exit_status = child.wait().map_err(Error::ClipWait)?;
+    if !exit_status.success() {
+        return Err(Error::ClipErr(backend.program));
+    }
+
+    process::Command::new("sh")
+        .arg("-c")
+        .arg(format!(
+            "trap '' INT TERM; sleep {} && {}",
+            CLEAR_AFTER_SECS, backend.clear
+        ))
+        .stdin(process::Stdio::null())
+        .stdout(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .spawn()
+        .map_err(Error::ClipClear)?;
+    *UNCLEARED.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    copied(selection);
+
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn serve(selection: Selection) {
+    crate::wayland::serve(selection == Selection::Primary, CLEAR_AFTER_SECS);
+}
+
+pub fn clear_interrupted() {
+    let backend = UNCLEARED.lock().unwrap_or_else(|e| e.into_inner()).take();
+    if let Some(backend) = backend {
+        let _ = process::Command::new("sh")
+            .arg("-c")
+            .arg(backend.clear)
+            .stdin(process::Stdio::null())
+            .stdout(process::Stdio::null())
+            .stderr(process::Stdio::null())
+            .status();
+    }
+}
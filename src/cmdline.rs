@@ -1,31 +1,850 @@
 use std::path::PathBuf;
+use std::str::FromStr;
+use structopt::clap::Shell;
 use structopt::StructOpt;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mark {
+    Valid,
+    Invalid,
+    Change,
+}
+
+impl Mark {
+    pub fn marker(self) -> &'static str {
+        match self {
+            Mark::Valid => "+",
+            Mark::Invalid => "-",
+            Mark::Change => "*",
+        }
+    }
+}
+
+impl FromStr for Mark {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "valid" => Ok(Mark::Valid),
+            "invalid" => Ok(Mark::Invalid),
+            "change" => Ok(Mark::Change),
+            _ => Err(format!("Unknown state {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Pwgen,
+    Os,
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pwgen" => Ok(Source::Pwgen),
+            "os" => Ok(Source::Os),
+            _ => Err(format!("Unknown password source {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Unknown log format {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnConflict {
+    Skip,
+    Overwrite,
+    Rename,
+    Ask,
+}
+
+impl FromStr for OnConflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(OnConflict::Skip),
+            "overwrite" => Ok(OnConflict::Overwrite),
+            "rename" => Ok(OnConflict::Rename),
+            "ask" => Ok(OnConflict::Ask),
+            _ => Err(format!("Unknown conflict strategy {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Credential {
+    Get,
+    Store,
+    Erase,
+}
+
+impl FromStr for Credential {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "get" => Ok(Credential::Get),
+            "store" => Ok(Credential::Store),
+            "erase" => Ok(Credential::Erase),
+            _ => Err(format!("Unknown credential action {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DockerCredential {
+    Get,
+    Store,
+    Erase,
+    List,
+}
+
+impl FromStr for DockerCredential {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "get" => Ok(DockerCredential::Get),
+            "store" => Ok(DockerCredential::Store),
+            "erase" => Ok(DockerCredential::Erase),
+            "list" => Ok(DockerCredential::List),
+            _ => Err(format!("Unknown credential action {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mapping {
+    pub key: String,
+    pub acc: String,
+    pub format: String,
+}
+
+impl FromStr for Mapping {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, entry) = s
+            .split_once('=')
+            .filter(|(key, entry)| !key.is_empty() && !entry.is_empty())
+            .ok_or_else(|| format!("Expected NAME=account[:format], got {}", s))?;
+        let (acc, format) = entry.split_once(':').unwrap_or((entry, "%P"));
+        Ok(Mapping {
+            key: key.to_string(),
+            acc: acc.to_string(),
+            format: format.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Selection {
+    #[default]
+    Clipboard,
+    Primary,
+    Tmux,
+}
+
+impl FromStr for Selection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "clipboard" => Ok(Selection::Clipboard),
+            "primary" => Ok(Selection::Primary),
+            "tmux" => Ok(Selection::Tmux),
+            _ => Err(format!("Unknown selection {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Name,
+    Link,
+    Username,
+    Password,
+    Answer(u32),
+}
+
+impl Field {
+    pub fn format(self) -> Option<&'static str> {
+        match self {
+            Field::Name => Some("%N"),
+            Field::Link => Some("%L"),
+            Field::Username => Some("%U"),
+            Field::Password => Some("%P"),
+            Field::Answer(_) => None,
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(Field::Name),
+            "link" => Ok(Field::Link),
+            "username" => Ok(Field::Username),
+            "password" => Ok(Field::Password),
+            _ => s
+                .strip_prefix('q')
+                .and_then(|question| question.parse().ok())
+                .map(Field::Answer)
+                .ok_or_else(|| format!("Unknown field {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Attach {
+    #[structopt(about = "Encrypt a file to the attach-key and attach it to an account")]
+    Add {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(help = "File to attach, at most 1 MiB")]
+        path: PathBuf,
+        #[structopt(long, help = "Attachment name, defaults to the file name")]
+        name: Option<String>,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Decrypt an attachment of an account")]
+    Get {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(help = "Attachment name")]
+        name: String,
+        #[structopt(short, long, help = "Write to this file instead of standard output")]
+        output: Option<PathBuf>,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(name = "ls", about = "List the attachments of an account")]
+    List {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+}
+
+impl Attach {
+    pub fn file(&self) -> Option<&PathBuf> {
+        match self {
+            Attach::Add { file, .. } | Attach::Get { file, .. } | Attach::List { file, .. } => {
+                file.as_ref()
+            }
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Export {
+    #[structopt(about = "Export account fields as a .env file")]
+    Dotenv {
+        #[structopt(
+            long = "map",
+            number_of_values = 1,
+            required = true,
+            help = "Set NAME to the fields of an account as NAME=account[:format], the format \
+                    defaults to %P"
+        )]
+        maps: Vec<Mapping>,
+        #[structopt(
+            short,
+            long,
+            help = "Write to this file with owner-only permissions instead of standard output"
+        )]
+        output: Option<PathBuf>,
+        #[structopt(long, help = "Encrypt with a passphrase using gpg --symmetric")]
+        encrypt: bool,
+        #[structopt(long, help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(
+        name = "k8s",
+        about = "Export account fields as a Kubernetes Secret manifest"
+    )]
+    K8s {
+        #[structopt(long, help = "Name of the Secret")]
+        name: String,
+        #[structopt(
+            long = "entry",
+            number_of_values = 1,
+            required = true,
+            help = "Set key NAME to the fields of an account as NAME=account[:format], the \
+                    format defaults to %P"
+        )]
+        entries: Vec<Mapping>,
+        #[structopt(long, help = "Encrypt with a passphrase using gpg --symmetric")]
+        encrypt: bool,
+        #[structopt(long, help = "Password file")]
+        file: Option<PathBuf>,
+    },
+}
+
+impl Export {
+    pub fn file(&self) -> Option<&PathBuf> {
+        match self {
+            Export::Dotenv { file, .. } | Export::K8s { file, .. } => file.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Import {
+    #[structopt(
+        about = "Merge the TOTP secrets of an andOTP or Aegis export into matching accounts"
+    )]
+    Oath {
+        #[structopt(help = "Unencrypted JSON export")]
+        backup: PathBuf,
+        #[structopt(
+            long,
+            default_value = "skip",
+            possible_values = &["skip", "overwrite", "rename", "ask"],
+            help = "What to do when an account already has another OTP secret, rename keeps both and ask prompts for each"
+        )]
+        on_conflict: OnConflict,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+}
+
+impl Import {
+    pub fn file(&self) -> Option<&PathBuf> {
+        match self {
+            Import::Oath { file, .. } => file.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Otp {
+    #[structopt(about = "Store the base32 TOTP secret of an account")]
+    Add {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(help = "Base32 secret, spaces and padding are ignored")]
+        secret: String,
+        #[structopt(long, help = "Number of digits in a code [default: 6]")]
+        digits: Option<u32>,
+        #[structopt(long, help = "Seconds each code is valid for [default: 30]")]
+        period: Option<u64>,
+        #[structopt(
+            long,
+            case_insensitive = true,
+            possible_values = &["SHA1", "SHA256", "SHA512"],
+            help = "Hash algorithm [default: SHA1]"
+        )]
+        algorithm: Option<String>,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(name = "ls", about = "List the accounts that have a TOTP secret")]
+    List {
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(name = "rm", about = "Remove the TOTP secret of an account")]
+    Remove {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+}
+
+impl Otp {
+    pub fn file(&self) -> Option<&PathBuf> {
+        match self {
+            Otp::Add { file, .. } | Otp::List { file } | Otp::Remove { file, .. } => file.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, StructOpt)]
+pub enum Stats {
+    #[structopt(about = "Count how many accounts use each username or email address")]
+    Usernames {
+        #[structopt(long, help = "Print the counts as JSON")]
+        json: bool,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+}
+
 #[derive(Debug, StructOpt)]
 pub enum Cmd {
+    #[structopt(about = "Print the password asked for by an ssh prompt, for use as SSH_ASKPASS")]
+    Askpass {
+        #[structopt(
+            help = "Prompt passed by ssh, key passphrases are looked up by the key path in the \
+                    link (e.g. ~/.ssh/id_ed25519) and logins by username and link host"
+        )]
+        prompt: String,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Manage small encrypted files attached to an account")]
+    Attach(Attach),
     #[structopt(about = "Check and print password stats")]
     Check {
+        #[structopt(
+            long,
+            help = "Also fail on warnings such as duplicate names or odd whitespace"
+        )]
+        strict: bool,
+        #[structopt(long, help = "List every entry with its line number and warnings")]
+        verbose: bool,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Print a shell completion script that completes account names")]
+    Completions {
+        #[structopt(possible_values = &["zsh", "fish"], help = "Shell to complete for")]
+        shell: Shell,
+    },
+    #[structopt(about = "Compress the password file with zstd")]
+    Compress {
+        #[structopt(long, help = "Decompress the password file instead")]
+        off: bool,
         #[structopt(help = "Password file")]
         file: Option<PathBuf>,
     },
+    #[structopt(about = "Derive a LessPass-compatible password from a master password")]
+    Derive {
+        #[structopt(help = "Site name, e.g. example.org")]
+        site: String,
+        #[structopt(help = "Login for the site")]
+        login: String,
+        #[structopt(long, default_value = "1", help = "Increase to get a new password")]
+        counter: u32,
+        #[structopt(
+            long,
+            help = "Password policy from the config file, defaults to LessPass's 16 characters"
+        )]
+        policy: Option<String>,
+        #[structopt(
+            long,
+            require_equals = true,
+            possible_values = &["clipboard", "primary", "tmux"],
+            help = "Copy the password to the clipboard, the primary selection or a tmux buffer instead of printing it"
+        )]
+        clip: Option<Option<Selection>>,
+    },
+    #[structopt(about = "Act as a docker credential helper for accounts tagged docker")]
+    DockerCredential {
+        #[structopt(
+            possible_values = &["get", "store", "erase", "list"],
+            help = "Credential helper action passed by docker"
+        )]
+        action: DockerCredential,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Run a command with account fields in its environment")]
+    Exec {
+        #[structopt(
+            long = "env",
+            number_of_values = 1,
+            required = true,
+            help = "Set NAME to the fields of an account as NAME=account[:format], the format \
+                    defaults to %P"
+        )]
+        envs: Vec<Mapping>,
+        #[structopt(long, help = "Password file")]
+        file: Option<PathBuf>,
+        #[structopt(required = true, help = "Command to run, after --")]
+        command: Vec<String>,
+    },
+    #[structopt(about = "Export account fields for other tools")]
+    Export(Export),
     #[structopt(name = "gen", about = "Generate a password")]
-    Generate,
+    Generate {
+        #[structopt(
+            long,
+            number_of_values = 3,
+            value_names = &["name", "link", "username"],
+            help = "Add a new account with the password and copy it to the clipboard"
+        )]
+        add: Option<Vec<String>>,
+        #[structopt(
+            long,
+            require_equals = true,
+            possible_values = &["clipboard", "primary", "tmux"],
+            help = "Copy the password to the clipboard, the primary selection or a tmux buffer instead of printing it"
+        )]
+        clip: Option<Option<Selection>>,
+        #[structopt(
+            long,
+            conflicts_with_all = &["clip", "add"],
+            help = "Print this many candidate passwords"
+        )]
+        count: Option<usize>,
+        #[structopt(long, help = "Password policy from the config file")]
+        policy: Option<String>,
+        #[structopt(long, help = "Exclude look-alike characters such as 0/O and 1/l/I")]
+        no_ambiguous: bool,
+        #[structopt(
+            long,
+            default_value = "pwgen",
+            possible_values = &["pwgen", "os"],
+            help = "Generate with pwgen or the internal generator"
+        )]
+        source: Source,
+        #[structopt(long, help = "Print the entropy of the password in bits to stderr")]
+        verbose: bool,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Act as a git credential helper, matching accounts by link host")]
+    GitCredential {
+        #[structopt(
+            possible_values = &["get", "store", "erase"],
+            help = "Credential helper action passed by git"
+        )]
+        action: Credential,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
     #[structopt(about = "Retrieve a password")]
     Get {
-        #[structopt(name = "account name", help = "Exact match for an account name")]
-        acc: String,
-        #[structopt(help = "Format: %N = Name, %L = Link, %U = Username, %P = Password")]
-        format: String,
-        #[structopt(help = "Password file")]
+        #[structopt(
+            name = "account name",
+            required = true,
+            help = "Exact match for an account name, without --format or --field \
+                    the arguments are <account name> <format> [file]"
+        )]
+        accs: Vec<String>,
+        #[structopt(
+            long,
+            help = "Format: %N = Name, %L = Link, %U = Username, %P = Password"
+        )]
+        format: Option<String>,
+        #[structopt(
+            long = "field",
+            number_of_values = 1,
+            help = "Print a single field, one line per --field: name, link, username, password \
+                    or q<n> for the answer to security question n"
+        )]
+        fields: Vec<Field>,
+        #[structopt(long, help = "Match the account names as URLs by registrable domain")]
+        url: bool,
+        #[structopt(
+            long,
+            help = "Also check the whole file while searching, failing on warnings"
+        )]
+        strict: bool,
+        #[structopt(short, long, help = "Do not print a trailing newline")]
+        no_newline: bool,
+        #[structopt(long, help = "Show passwords even when printing to a terminal")]
+        reveal: bool,
+        #[structopt(long, help = "Password file")]
         file: Option<PathBuf>,
     },
+    #[structopt(about = "Import secrets from other tools")]
+    Import(Import),
     #[structopt(name = "ls", about = "Search for passwords")]
     List {
-        #[structopt(help = "Query for an account name")]
+        #[structopt(
+            help = "Query for an account name, alias or link host, or a name prefix when it ends with /"
+        )]
         query: String,
+        #[structopt(long, help = "Search archived accounts instead")]
+        archived: bool,
+        #[structopt(long, help = "Show passwords even when printing to a terminal")]
+        reveal: bool,
+        #[structopt(long, help = "Print account names as a tree split on /")]
+        tree: bool,
+        #[structopt(
+            long,
+            conflicts_with = "tree",
+            help = "Print one JSON object per account"
+        )]
+        jsonl: bool,
+        #[structopt(
+            short = "0",
+            long = "null",
+            conflicts_with = "tree",
+            help = "Terminate each account with a NUL character instead of a newline"
+        )]
+        null: bool,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Mark an account as valid, invalid or needing change")]
+    Mark {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(
+            name = "state",
+            possible_values = &["valid", "invalid", "change"],
+            help = "New state of the account"
+        )]
+        mark: Mark,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Print accounts matching a URL as JSON, best matches first")]
+    MatchUrl {
+        #[structopt(help = "Page URL")]
+        url: String,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(name = "mv", about = "Rename an account")]
+    Move {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        old: String,
+        #[structopt(name = "new name", help = "New account name")]
+        new: String,
         #[structopt(help = "Password file")]
         file: Option<PathBuf>,
     },
+    #[structopt(about = "Print the names of the current accounts, one per line")]
+    Names {
+        #[structopt(
+            help = "Query for an account name, alias or link host, or a name prefix when it ends with /"
+        )]
+        query: Option<String>,
+        #[structopt(
+            short = "0",
+            long = "null",
+            help = "Terminate each name with a NUL character instead of a newline"
+        )]
+        null: bool,
+        #[structopt(long, help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Send a desktop notification for passwords that need changing")]
+    Notify {
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Open the link of an account")]
+    Open {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(
+            long,
+            require_equals = true,
+            possible_values = &["clipboard", "primary", "tmux"],
+            help = "Also copy the username to the clipboard, the primary selection or a tmux buffer"
+        )]
+        clip: Option<Option<Selection>>,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Manage the TOTP secrets of accounts")]
+    Otp(Otp),
+    #[structopt(about = "Print the unused 2FA recovery codes of an account")]
+    Recovery {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(
+            long = "use",
+            help = "Print the next unused recovery code and mark it as used"
+        )]
+        use_code: bool,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Replace {{ pw \"account\" \"format\" }} placeholders in a template")]
+    Render {
+        #[structopt(help = "Template file")]
+        template: PathBuf,
+        #[structopt(
+            short,
+            long,
+            help = "Write to this file with owner-only permissions instead of standard output"
+        )]
+        output: Option<PathBuf>,
+        #[structopt(long, help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(name = "rm", about = "Move an account to the archive")]
+    Remove {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Summarize passwords that need attention, fail if there are any")]
+    Report {
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Generate a new password for an account using its policy")]
+    Rotate {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(
+            long,
+            require_equals = true,
+            possible_values = &["clipboard", "primary", "tmux"],
+            help = "Copy the password to the clipboard, the primary selection or a tmux buffer instead of printing it"
+        )]
+        clip: Option<Option<Selection>>,
+        #[structopt(
+            long,
+            default_value = "pwgen",
+            possible_values = &["pwgen", "os"],
+            help = "Generate with pwgen or the internal generator"
+        )]
+        source: Source,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Refresh the detached signature of the password file")]
+    Sign {
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Export tagged accounts encrypted to a gpg or age recipient")]
+    Share {
+        #[structopt(
+            long = "tag",
+            required = true,
+            number_of_values = 1,
+            help = "Share the current accounts with this tag"
+        )]
+        tags: Vec<String>,
+        #[structopt(long, help = "Recipient gpg key or age public key (age1...)")]
+        to: String,
+        #[structopt(long, help = "Write to this file instead of standard output")]
+        output: Option<PathBuf>,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Show all details of an account")]
+    Show {
+        #[structopt(name = "account name", help = "Exact match for an account name")]
+        acc: String,
+        #[structopt(long, help = "Show the password instead of masking it")]
+        reveal: bool,
+        #[structopt(
+            long,
+            require_equals = true,
+            possible_values = &["clipboard", "primary", "tmux"],
+            help = "Copy the password to the clipboard, the primary selection or a tmux buffer"
+        )]
+        clip: Option<Option<Selection>>,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Split the password file into one file per account name prefix")]
+    Split {
+        #[structopt(
+            long,
+            help = "Split by the part of the account name before the first /"
+        )]
+        by_prefix: bool,
+        #[structopt(help = "New directory to read as a password file")]
+        dir: PathBuf,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Print detailed password statistics")]
+    Stats {
+        #[structopt(long, help = "Print statistics as JSON")]
+        json: bool,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+        #[structopt(subcommand)]
+        command: Option<Stats>,
+    },
+    #[structopt(
+        about = "Print the password of the sudo-account after confirming with pinentry, for use as SUDO_ASKPASS"
+    )]
+    SudoAskpass {
+        #[structopt(help = "Prompt passed by sudo")]
+        prompt: Option<String>,
+        #[structopt(long, help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[cfg(unix)]
+    #[structopt(about = "Answer systemd password requests whose Id is the link of an account")]
+    SystemdAgent {
+        #[structopt(long, help = "Keep answering new requests until interrupted")]
+        watch: bool,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Check that the password file was not modified outside of pw")]
+    Verify {
+        #[structopt(long, help = "Record the current password file as known-good")]
+        update: bool,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Revert the last change to the password file")]
+    Undo {
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+    #[structopt(about = "Show a QR code for joining a WiFi network tagged wifi")]
+    WifiQr {
+        #[structopt(name = "account name", help = "Exact match for the network name")]
+        acc: String,
+        #[structopt(short, long, help = "Write a PNG image to this file instead")]
+        output: Option<PathBuf>,
+        #[structopt(help = "Password file")]
+        file: Option<PathBuf>,
+    },
+}
+
+impl Cmd {
+    pub fn mutating(&self) -> bool {
+        matches!(
+            self,
+            Cmd::Attach(Attach::Add { .. })
+                | Cmd::Import(_)
+                | Cmd::Otp(Otp::Add { .. })
+                | Cmd::Otp(Otp::Remove { .. })
+                | Cmd::Compress { .. }
+                | Cmd::DockerCredential {
+                    action: DockerCredential::Store | DockerCredential::Erase,
+                    ..
+                }
+                | Cmd::Generate { add: Some(_), .. }
+                | Cmd::GitCredential {
+                    action: Credential::Store | Credential::Erase,
+                    ..
+                }
+                | Cmd::Mark { .. }
+                | Cmd::Move { .. }
+                | Cmd::Recovery { use_code: true, .. }
+                | Cmd::Remove { .. }
+                | Cmd::Rotate { .. }
+                | Cmd::Sign { .. }
+                | Cmd::Split { .. }
+                | Cmd::Undo { .. }
+                | Cmd::Verify { update: true, .. }
+        )
+    }
 }
 
 #[derive(Debug, StructOpt)]
@@ -33,6 +852,55 @@ pub enum Cmd {
 pub struct Pw {
     #[structopt(short, long, parse(from_occurrences))]
     pub verbose: u8,
+    #[structopt(
+        long,
+        global = true,
+        help = "Refuse to run commands that modify the password file"
+    )]
+    pub read_only: bool,
+    #[structopt(
+        long,
+        global = true,
+        help = "Show what would change in the password file without writing it"
+    )]
+    pub dry_run: bool,
+    #[structopt(
+        short,
+        long,
+        global = true,
+        help = "Only print the requested data and errors"
+    )]
+    pub quiet: bool,
+    #[structopt(
+        long,
+        global = true,
+        value_name = "path",
+        help = "Append log messages to this file instead of standard error, with secrets redacted"
+    )]
+    pub log_file: Option<PathBuf>,
+    #[structopt(
+        long,
+        global = true,
+        default_value = "text",
+        possible_values = &["text", "json"],
+        help = "Format of log messages, json writes one object per line"
+    )]
+    pub log_format: LogFormat,
+    #[structopt(
+        long,
+        hidden = true,
+        value_name = "prefix",
+        help = "Print the account names starting with prefix, for shell completion"
+    )]
+    pub complete_names: Option<String>,
+    #[structopt(
+        long,
+        hidden = true,
+        value_name = "selection",
+        possible_values = &["clipboard", "primary"],
+        help = "Serve the text read from standard input on the Wayland clipboard"
+    )]
+    pub serve_clipboard: Option<Selection>,
     #[structopt(subcommand)]
-    pub command: Cmd,
+    pub command: Option<Cmd>,
 }
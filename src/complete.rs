@@ -0,0 +1,64 @@
+use crate::cmdline::Pw;
+use crate::file::get_passfile;
+use crate::{parse, read};
+use std::collections::HashSet;
+use structopt::clap::Shell;
+use structopt::StructOpt;
+
+const ACCOUNT_COMMANDS: &[&str] = &[
+    "get", "mark", "mv", "open", "recovery", "rm", "rotate", "show", "wifi-qr",
+];
+
+const ZSH_NAMES: &str = r#"_pw_names() {
+    local -a names
+    names=(${(f)"$(pw --complete-names "$PREFIX" 2>/dev/null)"})
+    compadd -a names
+}
+
+"#;
+
+pub fn names(prefix: &str) {
+    let data = match get_passfile(None).and_then(read) {
+        Ok(data) => data,
+        Err(_) => return,
+    };
+
+    let mut seen = HashSet::new();
+    for entry in parse(&data).flatten() {
+        let data = entry.data();
+        for name in Some(data.name).into_iter().chain(data.aliases()) {
+            if name.starts_with(prefix) && seen.insert(name) {
+                println!("{}", name);
+            }
+        }
+    }
+}
+
+pub fn script(shell: Shell) {
+    let mut out = Vec::new();
+    Pw::clap().gen_completions_to("pw", shell, &mut out);
+    let script = String::from_utf8_lossy(&out);
+
+    match shell {
+        Shell::Zsh => {
+            let (header, body) = script.split_once('\n').unwrap_or_default();
+            println!("{}", header);
+            print!("{}", ZSH_NAMES);
+            for line in body.lines() {
+                if line.starts_with("':account name -- ") {
+                    let (spec, _) = line.rsplit_once(':').unwrap_or((line, ""));
+                    println!("{}:_pw_names' \\", spec);
+                } else {
+                    println!("{}", line);
+                }
+            }
+        }
+        _ => {
+            print!("{}", script);
+            println!(
+                "complete -c pw -n \"__fish_seen_subcommand_from {}\" -f -a \"(pw --complete-names (commandline -ct))\"",
+                ACCOUNT_COMMANDS.join(" ")
+            );
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use crate::err::Error;
+use crate::file;
+use crate::read;
+use log::info;
+use std::path::PathBuf;
+
+pub fn compress(file: PathBuf, off: bool) -> Result<(), Error> {
+    if file::compressed(&file) != off {
+        info!(
+            "{} is already {} (skip)",
+            file.display(),
+            if off { "uncompressed" } else { "compressed" }
+        );
+        return Ok(());
+    }
+
+    let data = read(&file)?;
+    file::store(&file, &data, !off)
+}
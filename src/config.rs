@@ -0,0 +1,253 @@
+use crate::cmdline::{Mark, Source};
+use crate::err::Error;
+use crate::gen::{Generator, Policy};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const MIN_LENGTH: usize = 12;
+const PROMPT_TIMEOUT: Duration = Duration::from_secs(180);
+
+#[derive(Debug, Clone)]
+pub struct Marker {
+    pub symbol: String,
+    pub name: String,
+    pub mark: Mark,
+}
+
+#[derive(Debug, Default)]
+pub struct Config {
+    file: Option<PathBuf>,
+    policies: HashMap<String, Policy>,
+    markers: Vec<Marker>,
+    audit_log: Option<PathBuf>,
+    log_file: Option<PathBuf>,
+    read_only: bool,
+    gpg_key: Option<String>,
+    attach_key: Option<String>,
+    sudo_account: Option<String>,
+    pwgen: Option<PathBuf>,
+    pwgen_args: Option<Vec<String>>,
+    min_length: Option<usize>,
+    prompt_timeout: Option<Duration>,
+    max_age: Option<Duration>,
+}
+
+pub fn parse_bool(num: usize, key: &str, value: &str) -> Result<bool, Error> {
+    match value {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(Error::Config(num, format!("{} must be yes or no", key))),
+    }
+}
+
+fn config_file() -> Option<PathBuf> {
+    let mut file = dirs::config_dir()?;
+    file.push("pw");
+    file.push("config");
+    Some(file)
+}
+
+impl Config {
+    pub fn load() -> Result<Self, Error> {
+        let file = match config_file() {
+            Some(file) => file,
+            None => return Ok(Config::default()),
+        };
+
+        match fs::read_to_string(&file) {
+            Ok(data) => {
+                let mut config = Self::parse(&data)?;
+                config.file = Some(file);
+                Ok(config)
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(Error::ConfigRead(e)),
+        }
+    }
+
+    fn parse(data: &str) -> Result<Self, Error> {
+        let mut config = Config::default();
+        let lines = data.lines().enumerate().filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        });
+
+        for (num, line) in lines {
+            let num = num + 1;
+            let mut fields = line.split_whitespace();
+            match fields.next() {
+                Some("policy") => {
+                    let name = fields
+                        .next()
+                        .ok_or_else(|| Error::Config(num, String::from("missing policy name")))?;
+                    let policy = Policy::parse(name, num, fields)?;
+                    config.policies.insert(name.to_string(), policy);
+                }
+                Some("marker") => {
+                    let symbol = fields
+                        .next()
+                        .ok_or_else(|| Error::Config(num, String::from("missing marker symbol")))?;
+                    if ["+", "-", "*"].contains(&symbol) {
+                        return Err(Error::Config(num, format!("marker {} is built in", symbol)));
+                    }
+                    let name = fields
+                        .next()
+                        .ok_or_else(|| Error::Config(num, String::from("missing marker name")))?;
+                    let mark = fields
+                        .next()
+                        .ok_or_else(|| Error::Config(num, String::from("missing marker state")))?
+                        .parse()
+                        .map_err(|e| Error::Config(num, e))?;
+                    config.markers.push(Marker {
+                        symbol: symbol.to_string(),
+                        name: name.to_string(),
+                        mark,
+                    });
+                }
+                Some("audit-log") => {
+                    let path = fields.next().ok_or_else(|| {
+                        Error::Config(num, String::from("missing audit log path"))
+                    })?;
+                    config.audit_log = Some(PathBuf::from(path));
+                }
+                Some("log-file") => {
+                    let path = fields
+                        .next()
+                        .ok_or_else(|| Error::Config(num, String::from("missing log file path")))?;
+                    config.log_file = Some(PathBuf::from(path));
+                }
+                Some("read-only") => {
+                    let value = fields.next().ok_or_else(|| {
+                        Error::Config(num, String::from("missing read-only value"))
+                    })?;
+                    config.read_only = parse_bool(num, "read-only", value)?;
+                }
+                Some("gpg-key") => {
+                    let key = fields
+                        .next()
+                        .ok_or_else(|| Error::Config(num, String::from("missing gpg key")))?;
+                    let key = key.trim_start_matches("0x").to_uppercase();
+                    if key.len() != 40 || !key.chars().all(|c| c.is_ascii_hexdigit()) {
+                        return Err(Error::Config(
+                            num,
+                            String::from("gpg-key must be a full 40 character fingerprint"),
+                        ));
+                    }
+                    config.gpg_key = Some(key);
+                }
+                Some("attach-key") => {
+                    let key = fields
+                        .next()
+                        .ok_or_else(|| Error::Config(num, String::from("missing attach key")))?;
+                    config.attach_key = Some(key.to_string());
+                }
+                Some("sudo-account") => {
+                    let name = fields.next().ok_or_else(|| {
+                        Error::Config(num, String::from("missing sudo account name"))
+                    })?;
+                    config.sudo_account = Some(name.to_string());
+                }
+                Some("pwgen-path") => {
+                    let path = fields
+                        .next()
+                        .ok_or_else(|| Error::Config(num, String::from("missing pwgen path")))?;
+                    config.pwgen = Some(PathBuf::from(path));
+                }
+                Some("pwgen-args") => config.pwgen_args = Some(fields.map(String::from).collect()),
+                Some("min-length") => {
+                    let length = fields
+                        .next()
+                        .and_then(|value| value.parse().ok())
+                        .ok_or_else(|| Error::Config(num, String::from("invalid min-length")))?;
+                    config.min_length = Some(length);
+                }
+                Some("prompt-timeout") => {
+                    let timeout = fields
+                        .next()
+                        .and_then(|value| humantime::parse_duration(value).ok())
+                        .filter(|timeout| !timeout.is_zero())
+                        .ok_or_else(|| {
+                            Error::Config(num, String::from("invalid prompt-timeout"))
+                        })?;
+                    config.prompt_timeout = Some(timeout);
+                }
+                Some("max-age") => {
+                    let age = fields
+                        .next()
+                        .and_then(|value| humantime::parse_duration(value).ok())
+                        .filter(|age| !age.is_zero())
+                        .ok_or_else(|| Error::Config(num, String::from("invalid max-age")))?;
+                    config.max_age = Some(age);
+                }
+                Some(key) => return Err(Error::Config(num, format!("unknown setting {}", key))),
+                None => {}
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn generator(&self, source: Source) -> Generator {
+        Generator::new(source, self.pwgen.clone(), self.pwgen_args.clone())
+    }
+
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref()
+    }
+
+    pub fn audit_log(&self) -> Option<&Path> {
+        self.audit_log.as_deref()
+    }
+
+    pub fn log_file(&self) -> Option<&Path> {
+        self.log_file.as_deref()
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn gpg_key(&self) -> Option<&str> {
+        self.gpg_key.as_deref()
+    }
+
+    pub fn attach_key(&self) -> Option<&str> {
+        self.attach_key.as_deref()
+    }
+
+    pub fn sudo_account(&self) -> Option<&str> {
+        self.sudo_account.as_deref()
+    }
+
+    pub fn min_length(&self) -> usize {
+        self.min_length.unwrap_or(MIN_LENGTH)
+    }
+
+    pub fn prompt_timeout(&self) -> Duration {
+        self.prompt_timeout.unwrap_or(PROMPT_TIMEOUT)
+    }
+
+    pub fn changed_before(&self) -> Option<String> {
+        let cutoff = SystemTime::now().checked_sub(self.max_age?)?;
+        let cutoff = humantime::format_rfc3339_seconds(cutoff).to_string();
+        Some(cutoff[..10].to_string())
+    }
+
+    pub fn markers(&self) -> &[Marker] {
+        &self.markers
+    }
+
+    pub fn policy(&self, name: Option<&str>) -> Result<Policy, Error> {
+        match name {
+            Some(name) => self
+                .policies
+                .get(name)
+                .cloned()
+                .ok_or_else(|| Error::UnknownPolicy(name.to_string())),
+            None => Ok(Policy::default()),
+        }
+    }
+}
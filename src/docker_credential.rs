@@ -0,0 +1,128 @@
+use crate::add::append_entry;
+use crate::audit;
+use crate::cmdline::DockerCredential;
+use crate::config::Config;
+use crate::err::Error;
+use crate::file;
+use crate::link;
+use crate::rm;
+use crate::{check_value, find_unique, parse, read, Entry, EntryData, Secret, VALID};
+use log::info;
+use serde_json::{json, Map, Value};
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+const TAG: &str = "docker";
+const NOT_FOUND: &str = "credentials not found in native keychain";
+
+fn read_input() -> Result<Secret<String>, Error> {
+    let mut input = Secret::new(String::new());
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(Error::CredentialRead)?;
+    Ok(input)
+}
+
+fn registries<'a>(data: &'a str) -> impl Iterator<Item = Result<EntryData<'a>, Error>> {
+    parse(data).filter_map(|entry| match entry {
+        Ok(Entry::Valid(entry)) if entry.tags().any(|tag| tag == TAG) => Some(Ok(entry)),
+        Ok(_) => None,
+        Err(e) => Some(Err(e)),
+    })
+}
+
+fn find<'a>(data: &'a str, server: &str) -> Result<Option<EntryData<'a>>, Error> {
+    let host = link::host(server).ok_or_else(|| Error::InvalidUrl(server.to_string()))?;
+    find_unique(data, VALID, server, |entry| {
+        entry.tags().any(|tag| tag == TAG) && link::host(entry.link).as_ref() == Some(&host)
+    })
+}
+
+fn store(file: PathBuf, input: &str) -> Result<(), Error> {
+    let request: Value = serde_json::from_str(input).map_err(Error::CredentialJson)?;
+    let field = |key: &str| {
+        request[key]
+            .as_str()
+            .ok_or_else(|| Error::InvalidValue("credential field", key.to_string()))
+    };
+    let (server, username, secret) = (field("ServerURL")?, field("Username")?, field("Secret")?);
+    let secret = Secret::new(secret.to_string());
+    check_value("link", server)?;
+    check_value("username", username)?;
+    check_value("password", &secret)?;
+
+    let data = read(&file)?;
+    let edited = match find(&data, server)? {
+        Some(entry) if entry.username == username && entry.password == *secret => return Ok(()),
+        Some(entry) => file::replace_line(&data, entry.line, |line| {
+            let line = file::replace_token(line, 3, username);
+            file::replace_token(&line, 4, &secret)
+        }),
+        None => {
+            let host = link::host(server).ok_or_else(|| Error::InvalidUrl(server.to_string()))?;
+            let name = format!("{}/{}", TAG, host);
+            if parse(&data)
+                .filter_map(Result::ok)
+                .any(|entry| entry.data().named(&name))
+            {
+                return Err(Error::Exists(name));
+            }
+            let tags = format!("tags={}", TAG);
+            append_entry(&data, &[&name, server, username, &secret, &tags])
+        }
+    };
+    file::write(&file, &edited)?;
+    info!("Stored the docker credentials for {}", server);
+
+    Ok(())
+}
+
+pub fn docker_credential(
+    file: PathBuf,
+    action: DockerCredential,
+    config: &Config,
+) -> Result<(), Error> {
+    if let DockerCredential::List = action {
+        let data = read(file)?;
+        let mut servers = Map::new();
+        for entry in registries(&data) {
+            let entry = entry?;
+            servers.insert(entry.link.to_string(), json!(entry.username));
+        }
+        println!("{}", Value::Object(servers));
+        return Ok(());
+    }
+
+    let input = read_input()?;
+    if let DockerCredential::Store = action {
+        return store(file, &input);
+    }
+
+    let server = input.trim();
+    let data = read(&file)?;
+    let entry = match find(&data, server)? {
+        Some(entry) => entry,
+        None if action == DockerCredential::Get => {
+            println!("{}", NOT_FOUND);
+            return Err(Error::NoMatches(server.to_string()));
+        }
+        None => return Ok(()),
+    };
+
+    if action == DockerCredential::Get {
+        audit::record(config, "docker-credential", &[entry.name])?;
+        let credentials = Secret::new(
+            json!({
+                "ServerURL": server,
+                "Username": entry.username,
+                "Secret": entry.password,
+            })
+            .to_string(),
+        );
+        println!("{}", *credentials);
+    } else {
+        rm::remove(file.clone(), entry.name.to_string())?;
+    }
+
+    Ok(())
+}
@@ -6,20 +6,86 @@ use thiserror::Error;
 pub enum Error {
     #[error("Could not initialize logger, {0}")]
     LogInit(#[from] log::SetLoggerError),
+    #[error("Could not install signal handler: {0}")]
+    Signal(#[from] ctrlc::Error),
+    #[error("Could not read config file: {0}")]
+    ConfigRead(io::Error),
+    #[error("Invalid config at line {0}, {1}")]
+    Config(usize, String),
+    #[error("No password policy named {0} in the config file")]
+    UnknownPolicy(String),
+    #[error("Policy {0} cannot be used with pwgen-args from the config file, remove pwgen-args or use --source os")]
+    PwGenArgsPolicy(String),
     #[error("Could not read password file: {0}")]
     PassFile(io::Error),
-    #[error("Invalid entry at line {0}, missing marker")]
-    MissingMarker(usize),
-    #[error("Invalid entry at line {0}, missing name")]
-    MissingName(usize),
-    #[error("Invalid entry at line {0}, missing link")]
-    MissingLink(usize),
-    #[error("Invalid entry at line {0}, missing username")]
-    MissingUsername(usize),
-    #[error("Invalid entry at line {0}, missing password")]
-    MissingPassword(usize),
-    #[error("Invalid entry at line {0}, invalid marker {0}")]
-    InvalidEntryMarker(usize, String),
+    #[error("Refusing to modify the password file in read-only mode")]
+    ReadOnly,
+    #[error("Could not write password file: {0}")]
+    PassFileWrite(io::Error),
+    #[error("Could not compress password file: {0}")]
+    Compress(io::Error),
+    #[error("Could not decompress password file: {0}")]
+    Decompress(io::Error),
+    #[error("Could not write checksum file: {0}")]
+    ChecksumWrite(io::Error),
+    #[error("Could not read checksum file: {0}")]
+    ChecksumRead(io::Error),
+    #[error("No checksum found at {0}, run pw verify --update")]
+    NoChecksum(String),
+    #[error("{0} was modified outside of pw since it was last written")]
+    Modified(String),
+    #[error("Could not back up password file: {0}")]
+    BackupWrite(io::Error),
+    #[error("No backup found at {0}")]
+    NoBackup(String),
+    #[error("{0} is a directory store, undo one of the files in it instead")]
+    UndoDir(String),
+    #[error("Could not write audit log: {0}")]
+    AuditWrite(io::Error),
+    #[error("Could not open the log file: {0}")]
+    LogFile(io::Error),
+    #[error("Missing how to split, use --by-prefix")]
+    SplitMode,
+    #[error("Could not create the split directory: {0}")]
+    SplitDir(io::Error),
+    #[error("Could not write archive file: {0}")]
+    ArchiveWrite(io::Error),
+    #[error("Invalid entry at line {0}, column {1}: missing marker")]
+    MissingMarker(usize, usize),
+    #[error("Invalid entry at line {0}, column {1}: missing name")]
+    MissingName(usize, usize),
+    #[error("Invalid entry at line {0}, column {1}: missing link")]
+    MissingLink(usize, usize),
+    #[error("Invalid entry at line {0}, column {1}: missing username")]
+    MissingUsername(usize, usize),
+    #[error("Invalid entry at line {0}, column {1}: missing password")]
+    MissingPassword(usize, usize),
+    #[error("Invalid entry at line {0}, column {1}: malformed field (expected key=value)")]
+    InvalidField(usize, usize),
+    #[error("Invalid entry at line {0}, column {1}: invalid marker {2}")]
+    InvalidEntryMarker(usize, usize, String),
+    #[error("Invalid entry at line {0}, column {1}: unexpected character {2:?}")]
+    ControlChar(usize, usize, char),
+    #[error("Invalid entry at line {0}, column {1}: invalid UTF-8")]
+    InvalidUtf8(usize, usize),
+    #[error("Invalid entry at line {0}, {1} is already a current account on line {2}")]
+    Duplicate(usize, String, usize),
+    #[error("Found {0} invalid entries")]
+    InvalidEntries(usize),
+    #[error("Found {0} warnings in strict mode")]
+    Warnings(usize),
+    #[error("{0} has no answer to security question {1}")]
+    NoAnswer(String, u32),
+    #[error("No recovery codes left for {0}")]
+    NoRecoveryCodes(String),
+    #[error("Could not read the credential request: {0}")]
+    CredentialRead(io::Error),
+    #[error("Invalid credential request: {0}")]
+    CredentialJson(serde_json::Error),
+    #[error("Could not read the master password: {0}")]
+    MasterPassword(io::Error),
+    #[error("Could not read random data: {0}")]
+    Random(getrandom::Error),
     #[error("Could not run pwgen: {0}")]
     PwGenSpawn(io::Error),
     #[error("Could not wait on pwgen process: {0}")]
@@ -36,12 +102,147 @@ pub enum Error {
     PwGenStdoutErr(io::Error),
     #[error("Pwgen died from a signal")]
     PwGenDied,
-    #[error("Found more than 1 match for {0}")]
-    Mismatch(String),
+    #[error("Could not run {0}: {1}")]
+    ClipSpawn(&'static str, io::Error),
+    #[error("Could not write to the clipboard: {0}")]
+    ClipWrite(io::Error),
+    #[error("Could not wait on clipboard process: {0}")]
+    ClipWait(io::Error),
+    #[error("Could not copy to the clipboard, {0} failed")]
+    ClipErr(&'static str),
+    #[error("Could not schedule clearing the clipboard: {0}")]
+    ClipClear(io::Error),
+    #[error("Could not start the clipboard process: {0}")]
+    ClipFork(io::Error),
+    #[error("Could not use the Wayland clipboard: {0}")]
+    Wayland(String),
+    #[error("Invalid OTP secret, expected base32")]
+    InvalidOtpSecret,
+    #[error("{0} already has an OTP secret")]
+    OtpExists(String),
+    #[error("{0} has no OTP secret")]
+    NoOtp(String),
+    #[error("Could not read the export: {0}")]
+    ImportRead(io::Error),
+    #[error("Invalid export: {0}")]
+    OathJson(serde_json::Error),
+    #[error("Invalid export, {0}")]
+    OathFormat(&'static str),
+    #[error("Asking about conflicts needs a terminal")]
+    NotInteractive,
+    #[error("Could not read the answer: {0}")]
+    Prompt(io::Error),
+    #[error("The answer is not valid UTF-8")]
+    PromptUtf8,
+    #[error("No answer within {0} seconds")]
+    PromptTimeout(u64),
+    #[error("Interrupted")]
+    Interrupted,
+    #[error("{0} is not tagged wifi")]
+    NotWifi(String),
+    #[error("Could not run {0}: {1}")]
+    QrSpawn(&'static str, io::Error),
+    #[error("Could not write to {0}: {1}")]
+    QrWrite(&'static str, io::Error),
+    #[error("Could not wait on {0}: {1}")]
+    QrWait(&'static str, io::Error),
+    #[error("Could not create the QR code, {0} failed")]
+    QrErr(&'static str),
+    #[error("Could not run gpg: {0}")]
+    GpgSpawn(io::Error),
+    #[error("Could not sign the password file, gpg failed")]
+    GpgErr,
+    #[error("No gpg-key in the config file")]
+    NoGpgKey,
+    #[error("No attach-key in the config file")]
+    NoAttachKey,
+    #[error("No signature found at {0}, run pw sign")]
+    NoSignature(String),
+    #[error("Signature {0} does not verify against the configured key")]
+    BadSignature(String),
+    #[error("Could not run {0}: {1}")]
+    ShareSpawn(&'static str, io::Error),
+    #[error("Could not write the shared accounts: {0}")]
+    ShareWrite(io::Error),
+    #[error("Could not wait on the encryption process: {0}")]
+    ShareWait(io::Error),
+    #[error("Could not encrypt the shared accounts, {0} failed")]
+    ShareErr(&'static str),
+    #[error("Could not read the attachment: {0}")]
+    AttachRead(io::Error),
+    #[error("Could not write the attachment: {0}")]
+    AttachWrite(io::Error),
+    #[error("{0} is larger than the {1} bytes allowed for attachments")]
+    AttachmentTooLarge(String, u64),
+    #[error("{0} has no attachment named {1}")]
+    NoAttachment(String, String),
+    #[error("Could not {0} the attachment, gpg failed")]
+    AttachErr(&'static str),
+    #[error("Could not run pinentry: {0}")]
+    PinentrySpawn(io::Error),
+    #[error("No sudo-account in the config file")]
+    NoSudoAccount,
+    #[error("Access to the password of {0} was denied")]
+    Declined(String),
+    #[error("Could not read the password requests: {0}")]
+    AgentRead(io::Error),
+    #[error("Could not answer the password request: {0}")]
+    AgentReply(io::Error),
+    #[error("Could not read the template: {0}")]
+    TemplateRead(io::Error),
+    #[error("Invalid template at line {0}, {1}")]
+    Template(usize, String),
+    #[error("Could not write the rendered template: {0}")]
+    RenderWrite(io::Error),
+    #[error("Could not write the export: {0}")]
+    ExportWrite(io::Error),
+    #[error("Could not encrypt the export, gpg failed")]
+    ExportEncrypt,
+    #[error("Invalid Kubernetes {0} {1:?}")]
+    InvalidK8s(&'static str, String),
+    #[error("Missing a command to run")]
+    MissingCommand,
+    #[error("Could not run {0}: {1}")]
+    ExecSpawn(String, io::Error),
+    #[error("Could not run {0}: {1}")]
+    OpenSpawn(&'static str, io::Error),
+    #[error("Could not open link, {0} failed")]
+    OpenErr(&'static str),
+    #[error("Could not find a host in {0}")]
+    InvalidUrl(String),
+    #[error("Invalid {0} {1:?}, must be non-empty without whitespace")]
+    InvalidValue(&'static str, String),
+    #[error("An account named {0} already exists")]
+    Exists(String),
+    #[error("Missing a format, use <account name> <format>, --format or --field")]
+    MissingFormat,
+    #[error("Found more than 1 match for {0}, on lines {1} and {2}")]
+    Mismatch(String, usize, usize),
     #[error("No matches found for {0}")]
     NoMatches(String),
     #[error("No default password file found in HOME/.passfile")]
     NoPassFile,
+    #[error("Could not send desktop notification: {0}")]
+    Notify(notify_rust::error::Error),
+    #[error("{0} password(s) need attention")]
+    ActionNeeded(usize),
+}
+
+impl Error {
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match *self {
+            Error::MissingMarker(line, column)
+            | Error::MissingName(line, column)
+            | Error::MissingLink(line, column)
+            | Error::MissingUsername(line, column)
+            | Error::MissingPassword(line, column)
+            | Error::InvalidField(line, column)
+            | Error::InvalidEntryMarker(line, column, _)
+            | Error::ControlChar(line, column, _)
+            | Error::InvalidUtf8(line, column) => Some((line, column)),
+            _ => None,
+        }
+    }
 }
 
 impl Debug for Error {
@@ -0,0 +1,67 @@
+use crate::audit;
+use crate::cmdline::Mapping;
+use crate::config::Config;
+use crate::err::Error;
+use crate::{find_all, fmt_entry, read, Secret, VALID};
+use std::path::Path;
+use std::process;
+
+pub fn fields(
+    file: &Path,
+    fields: &[(&str, &str)],
+    command: &str,
+    config: &Config,
+) -> Result<Vec<Secret<String>>, Error> {
+    let queries: Vec<&str> = fields.iter().map(|(acc, _)| *acc).collect();
+    let data = read(file)?;
+    let entries = find_all(&data, VALID, &queries, |entry, i| entry.named(queries[i]))?;
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name).collect();
+    audit::record(config, command, &names)?;
+    Ok(fields
+        .iter()
+        .zip(&entries)
+        .map(|((_, format), entry)| Secret::new(fmt_entry(format, entry, true)))
+        .collect())
+}
+
+pub fn resolve(
+    file: &Path,
+    mappings: &[Mapping],
+    command: &str,
+    config: &Config,
+) -> Result<Vec<(String, Secret<String>)>, Error> {
+    let queries: Vec<(&str, &str)> = mappings
+        .iter()
+        .map(|mapping| (mapping.acc.as_str(), mapping.format.as_str()))
+        .collect();
+    let keys = mappings.iter().map(|mapping| mapping.key.clone());
+    Ok(keys.zip(fields(file, &queries, command, config)?).collect())
+}
+
+pub fn exec(
+    file: &Path,
+    envs: &[Mapping],
+    command: &[String],
+    config: &Config,
+) -> Result<i32, Error> {
+    let vars = resolve(file, envs, "exec", config)?;
+    let (program, args) = command.split_first().ok_or(Error::MissingCommand)?;
+    let mut command = process::Command::new(program);
+    command
+        .args(args)
+        .envs(vars.iter().map(|(key, value)| (key, value.as_str())));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        Err(Error::ExecSpawn(program.clone(), command.exec()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let exit_status = command
+            .status()
+            .map_err(|e| Error::ExecSpawn(program.clone(), e))?;
+        Ok(exit_status.code().unwrap_or(1))
+    }
+}
@@ -0,0 +1,185 @@
+use crate::cmdline::{Export, Mapping};
+use crate::config::Config;
+use crate::err::Error;
+use crate::exec;
+use crate::file;
+use crate::Secret;
+use log::info;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::thread;
+
+const GPG: &str = "gpg";
+
+const DOTENV_HEADER: &str =
+    "# Generated by pw export dotenv. This file contains secrets, do not commit it.\n";
+
+fn quote(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' | '"' | '$' | '`' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn dotenv(file: &Path, maps: &[Mapping], config: &Config) -> Result<Secret<String>, Error> {
+    let mut out = Secret::new(String::from(DOTENV_HEADER));
+    for (key, value) in exec::resolve(file, maps, "export", config)? {
+        out.push_str(&key);
+        out.push('=');
+        out.push_str(&Secret::new(quote(&value)));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(char::from(BASE64[(n >> (18 - 6 * i)) as usize & 63]));
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn k8s_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        })
+}
+
+fn k8s_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= 253
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+fn k8s(
+    file: &Path,
+    name: &str,
+    entries: &[Mapping],
+    config: &Config,
+) -> Result<Secret<String>, Error> {
+    if !k8s_name(name) {
+        return Err(Error::InvalidK8s("secret name", name.to_string()));
+    }
+    if let Some(entry) = entries.iter().find(|entry| !k8s_key(&entry.key)) {
+        return Err(Error::InvalidK8s("secret key", entry.key.clone()));
+    }
+
+    let mut out = Secret::new(format!(
+        "apiVersion: v1\nkind: Secret\nmetadata:\n  name: {}\ntype: Opaque\ndata:\n",
+        name
+    ));
+    for (key, value) in exec::resolve(file, entries, "export", config)? {
+        out.push_str(&format!("  {}: ", key));
+        out.push_str(&Secret::new(base64(value.as_bytes())));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn encrypt(data: &str) -> Result<String, Error> {
+    let mut child = process::Command::new(GPG)
+        .arg("--symmetric")
+        .arg("--armor")
+        .args(["--output", "-"])
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .spawn()
+        .map_err(Error::GpgSpawn)?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let data = Secret::new(data.to_string());
+        thread::spawn(move || stdin.write_all(data.as_bytes()));
+    }
+
+    let out = child.wait_with_output().map_err(Error::GpgSpawn)?;
+    if !out.status.success() {
+        return Err(Error::ExportEncrypt);
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).into_owned())
+}
+
+fn output(data: &str, output: Option<PathBuf>, encrypt: bool) -> Result<(), Error> {
+    let encrypted;
+    let data = if encrypt {
+        encrypted = self::encrypt(data)?;
+        &encrypted
+    } else {
+        data
+    };
+    match output {
+        Some(output) => {
+            file::write_private(&output, data.as_bytes()).map_err(Error::ExportWrite)?;
+            info!("Exported to {}", output.display());
+        }
+        None => print!("{}", data),
+    }
+    Ok(())
+}
+
+pub fn export(file: PathBuf, command: Export, config: &Config) -> Result<(), Error> {
+    match command {
+        Export::Dotenv {
+            maps,
+            output: out,
+            encrypt,
+            ..
+        } => output(&dotenv(&file, &maps, config)?, out, encrypt),
+        Export::K8s {
+            name,
+            entries,
+            encrypt,
+            ..
+        } => output(&k8s(&file, &name, &entries, config)?, None, encrypt),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::base64;
+
+    #[test]
+    fn base64_tails() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+}
@@ -1,13 +1,30 @@
 use crate::err::Error;
+use crate::split;
+use crate::Secret;
+use crate::{column, read};
 use log::info;
-use std::path::PathBuf;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const ZSTD_LEVEL: i32 = 19;
 
 fn default_passfile() -> Option<PathBuf> {
     let mut passfile = dirs::home_dir()?;
 
     passfile.push(".passfile");
 
-    if passfile.is_file() {
+    if passfile.exists() {
         return Some(passfile);
     }
 
@@ -24,3 +41,327 @@ pub fn get_passfile(file: Option<PathBuf>) -> Result<PathBuf, Error> {
         Err(Error::NoPassFile)
     }
 }
+
+pub fn with_suffix(file: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(file);
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn options() -> fs::OpenOptions {
+    #[allow(unused_mut)]
+    let mut options = fs::OpenOptions::new();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    options
+}
+
+fn missing<'a>(from: &'a str, to: &str) -> Vec<&'a str> {
+    let mut counts = HashMap::new();
+    for line in to.lines() {
+        *counts.entry(line).or_insert(0) += 1;
+    }
+
+    let mut missing = Vec::new();
+    for line in from.lines() {
+        match counts.get_mut(line) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => missing.push(line),
+        }
+    }
+    missing
+}
+
+pub fn describe(line: &str) -> String {
+    let mut fields = line.split_whitespace();
+    match (fields.next(), fields.next()) {
+        (Some(marker), Some(name)) if !marker.starts_with('#') => {
+            format!("{} {}", marker, name)
+        }
+        _ => String::from("(comment or blank line)"),
+    }
+}
+
+pub fn print_changes(from: &str, to: &str) {
+    for line in missing(from, to) {
+        println!("  - {}", describe(line));
+    }
+    for line in missing(to, from) {
+        println!("  + {}", describe(line));
+    }
+}
+
+pub fn set_dry_run(dry_run: bool) {
+    DRY_RUN.store(dry_run, Ordering::Relaxed);
+}
+
+pub fn dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+pub fn compressed(file: &Path) -> bool {
+    let mut magic = [0; 4];
+    fs::File::open(file)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok()
+        && magic == ZSTD_MAGIC
+}
+
+pub fn decode(bytes: Vec<u8>) -> Result<Secret<Vec<u8>>, Error> {
+    let bytes = Secret::new(bytes);
+    if !bytes.starts_with(&ZSTD_MAGIC) {
+        return Ok(bytes);
+    }
+    zstd::decode_all(&bytes[..])
+        .map(Secret::new)
+        .map_err(Error::Decompress)
+}
+
+pub fn load(file: &Path) -> Result<Secret<Vec<u8>>, Error> {
+    if file.is_dir() {
+        return split::load(file);
+    }
+    decode(fs::read(file).map_err(Error::PassFile)?)
+}
+
+pub fn scan(
+    file: &Path,
+    mut visit: impl FnMut(usize, &str) -> Result<(), Error>,
+) -> Result<(), Error> {
+    if file.is_dir() || compressed(file) {
+        let data = read(file)?;
+        for (num, line) in data.lines().enumerate() {
+            visit(num + 1, line)?;
+        }
+        return Ok(());
+    }
+
+    let mut reader = fs::File::open(file).map_err(Error::PassFile)?;
+    let mut buf = Secret::new(vec![0; 8192]);
+    let mut line = Secret::new(Vec::new());
+    let mut num = 0;
+    loop {
+        let len = match reader.read(&mut buf) {
+            Ok(len) => len,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(Error::PassFile(e)),
+        };
+        if len == 0 {
+            if !line.is_empty() {
+                visit_bytes(num + 1, &line, &mut visit)?;
+            }
+            return Ok(());
+        }
+        for chunk in buf[..len].split_inclusive(|&byte| byte == b'\n') {
+            extend(&mut line, chunk);
+            if line.ends_with(b"\n") {
+                num += 1;
+                visit_bytes(num, &line, &mut visit)?;
+                line.clear();
+            }
+        }
+    }
+}
+
+fn extend(line: &mut Secret<Vec<u8>>, bytes: &[u8]) {
+    let len = line.len() + bytes.len();
+    if len > line.capacity() {
+        let mut grown = Secret::new(Vec::with_capacity(len.max(2 * line.capacity())));
+        grown.extend_from_slice(line);
+        *line = grown;
+    }
+    line.extend_from_slice(bytes);
+}
+
+fn visit_bytes(
+    num: usize,
+    line: &[u8],
+    visit: &mut impl FnMut(usize, &str) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut bytes = line;
+    if let Some(stripped) = bytes.strip_suffix(b"\n") {
+        bytes = stripped.strip_suffix(b"\r").unwrap_or(stripped);
+    }
+    match str::from_utf8(bytes) {
+        Ok(text) => visit(num, text),
+        Err(e) => {
+            let valid = String::from_utf8_lossy(&bytes[..e.valid_up_to()]);
+            Err(Error::InvalidUtf8(num, column(&valid, valid.len())))
+        }
+    }
+}
+
+pub fn write(file: &Path, data: &str) -> Result<(), Error> {
+    if file.is_dir() {
+        return split::store(file, data);
+    }
+    store(file, data, compressed(file))
+}
+
+pub fn store(file: &Path, data: &str, compress: bool) -> Result<(), Error> {
+    if dry_run() {
+        let current = if file.is_file() {
+            read(file)?
+        } else {
+            Secret::default()
+        };
+        println!("Would write {}:", file.display());
+        print_changes(&current, data);
+        return Ok(());
+    }
+
+    let bytes = Secret::new(if compress {
+        zstd::encode_all(data.as_bytes(), ZSTD_LEVEL).map_err(Error::Compress)?
+    } else {
+        data.as_bytes().to_vec()
+    });
+
+    let tmp = with_suffix(file, ".tmp");
+
+    let mut out = options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&tmp)
+        .map_err(Error::PassFileWrite)?;
+    out.write_all(&bytes)
+        .and_then(|()| out.sync_all())
+        .map_err(Error::PassFileWrite)?;
+
+    if let Ok(metadata) = fs::metadata(file) {
+        fs::set_permissions(&tmp, metadata.permissions()).map_err(Error::PassFileWrite)?;
+        fs::copy(file, with_suffix(file, ".bak")).map_err(Error::BackupWrite)?;
+    }
+
+    fs::rename(&tmp, file).map_err(Error::PassFileWrite)?;
+    info!("Wrote password file at {}", file.display());
+
+    write_checksum(file, &bytes)
+}
+
+pub fn checksum(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+pub fn write_checksum(file: &Path, data: &[u8]) -> Result<(), Error> {
+    let sum = with_suffix(file, ".sum");
+    if dry_run() {
+        println!("Would write {}", sum.display());
+        return Ok(());
+    }
+    fs::write(&sum, checksum(data) + "\n").map_err(Error::ChecksumWrite)
+}
+
+pub fn write_private(file: &Path, data: &[u8]) -> io::Result<()> {
+    let mut out = options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(file)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        out.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+
+    out.write_all(data)?;
+    out.sync_all()
+}
+
+pub fn open_append(file: &Path) -> io::Result<fs::File> {
+    options().append(true).create(true).open(file)
+}
+
+pub fn append(file: &Path, line: &str) -> io::Result<()> {
+    let mut out = open_append(file)?;
+    out.write_all(line.as_bytes())?;
+    out.write_all(b"\n")?;
+    out.sync_all()
+}
+
+pub fn line_ending(data: &str) -> &'static str {
+    match data.find('\n') {
+        Some(i) if data[..i].ends_with('\r') => "\r\n",
+        _ => "\n",
+    }
+}
+
+pub fn replace_line(data: &str, num: usize, edit: impl FnOnce(&str) -> String) -> Secret<String> {
+    let mut out = Secret::new(String::with_capacity(data.len()));
+    let mut edit = Some(edit);
+    for (i, line) in data.split_inclusive('\n').enumerate() {
+        match edit.take() {
+            Some(edit) if i + 1 == num => {
+                let content = line.trim_end_matches(&['\r', '\n'][..]);
+                out.push_str(&Secret::new(edit(content)));
+                out.push_str(&line[content.len()..]);
+            }
+            pending => {
+                edit = pending;
+                out.push_str(line);
+            }
+        }
+    }
+    out
+}
+
+pub fn remove_line(data: &str, num: usize) -> (Secret<String>, Secret<String>) {
+    let mut out = Secret::new(String::with_capacity(data.len()));
+    let mut removed = Secret::new(String::new());
+    for (i, line) in data.split_inclusive('\n').enumerate() {
+        if i + 1 == num {
+            removed.push_str(line.trim_end_matches(&['\r', '\n'][..]));
+        } else {
+            out.push_str(line);
+        }
+    }
+    (out, removed)
+}
+
+fn token_range(line: &str, index: usize) -> Option<Range<usize>> {
+    let token = line.split_whitespace().nth(index)?;
+    let start = token.as_ptr() as usize - line.as_ptr() as usize;
+    Some(start..start + token.len())
+}
+
+pub fn remove_token(line: &str, index: usize) -> String {
+    let mut out = String::from(line);
+    if let Some(range) = token_range(line, index) {
+        let start = line[..range.start].trim_end().len();
+        out.replace_range(start..range.end, "");
+    }
+    out
+}
+
+pub fn replace_token(line: &str, index: usize, value: &str) -> String {
+    let mut out = String::from(line);
+    if let Some(range) = token_range(line, index) {
+        out.replace_range(range, value);
+    }
+    out
+}
+
+pub fn set_field(line: &str, key: &str, value: &str) -> String {
+    let field = format!("{}={}", key, value);
+    let index = line
+        .split_whitespace()
+        .enumerate()
+        .skip(5)
+        .find(|(_, token)| token.split_once('=').is_some_and(|(k, _)| k == key))
+        .map(|(index, _)| index);
+    match index {
+        Some(index) => replace_token(line, index, &field),
+        None => format!("{} {}", line.trim_end(), field),
+    }
+}
+
+pub fn today() -> String {
+    let now = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    now[..10].to_string()
+}
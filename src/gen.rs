@@ -1,12 +1,270 @@
+use crate::clip;
+use crate::cmdline::{Selection, Source};
+use crate::config::parse_bool;
 use crate::err::Error;
-use log::info;
-use std::io::Read;
+use crate::{redacted, Secret};
+use log::{info, warn};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::process;
 
-pub fn generate() -> Result<(), Error> {
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const UPPERCASE: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DIGITS: &str = "0123456789";
+const SYMBOLS: &str = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+const AMBIGUOUS: &str = "B8G6I1l0OQDS5Z2";
+
+#[derive(Debug, Clone)]
+pub struct Policy {
+    name: Option<String>,
+    length: usize,
+    capitals: bool,
+    digits: bool,
+    symbols: bool,
+    ambiguous: bool,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            name: None,
+            length: 34,
+            capitals: true,
+            digits: true,
+            symbols: true,
+            ambiguous: false,
+        }
+    }
+}
+
+impl Policy {
+    pub fn lesspass() -> Self {
+        Policy {
+            length: 16,
+            ..Policy::default()
+        }
+    }
+
+    pub fn parse<'a>(
+        name: &str,
+        num: usize,
+        fields: impl Iterator<Item = &'a str>,
+    ) -> Result<Self, Error> {
+        let mut policy = Policy {
+            name: Some(name.to_string()),
+            ..Policy::default()
+        };
+        for field in fields {
+            let (key, value) = field
+                .split_once('=')
+                .ok_or_else(|| Error::Config(num, String::from("expected key=value")))?;
+            match key {
+                "length" => {
+                    policy.length = value
+                        .parse()
+                        .ok()
+                        .filter(|length| *length > 0)
+                        .ok_or_else(|| Error::Config(num, String::from("invalid length")))?
+                }
+                "capitals" => policy.capitals = parse_bool(num, key, value)?,
+                "digits" => policy.digits = parse_bool(num, key, value)?,
+                "symbols" => policy.symbols = parse_bool(num, key, value)?,
+                "ambiguous" => policy.ambiguous = parse_bool(num, key, value)?,
+                _ => {
+                    return Err(Error::Config(
+                        num,
+                        format!("unknown policy setting {}", key),
+                    ))
+                }
+            }
+        }
+        let shortest = policy.classes().len() + if policy.symbols { 2 } else { 0 };
+        if policy.length < shortest {
+            return Err(Error::Config(
+                num,
+                format!(
+                    "length must be at least {} for these character classes",
+                    shortest
+                ),
+            ));
+        }
+        Ok(policy)
+    }
+
+    pub fn no_ambiguous(mut self) -> Self {
+        self.ambiguous = false;
+        self
+    }
+
+    fn pwgen_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        args.push(String::from(if self.capitals { "-c" } else { "-A" }));
+        args.push(String::from(if self.digits { "-n" } else { "-0" }));
+        if self.symbols {
+            args.push(String::from("-y"));
+        }
+        args.push(String::from("-s"));
+        if !self.ambiguous {
+            args.push(String::from("-B"));
+        }
+        args.push(String::from("-1"));
+        args.push(self.length.to_string());
+        args.push(String::from("1"));
+        args
+    }
+
+    fn classes(&self) -> Vec<&'static str> {
+        let mut classes = vec![LOWERCASE];
+        if self.capitals {
+            classes.push(UPPERCASE);
+        }
+        if self.digits {
+            classes.push(DIGITS);
+        }
+        if self.symbols {
+            classes.push(SYMBOLS);
+        }
+        classes
+    }
+
+    fn alphabet(&self) -> Vec<char> {
+        self.classes()
+            .iter()
+            .flat_map(|class| class.chars())
+            .filter(|c| self.ambiguous || !AMBIGUOUS.contains(*c))
+            .collect()
+    }
+
+    fn accepts(&self, password: &str) -> bool {
+        let edge_symbol = |c: Option<char>| c.is_some_and(|c| c.is_ascii_punctuation());
+        if edge_symbol(password.chars().next()) || edge_symbol(password.chars().last()) {
+            return false;
+        }
+
+        self.classes()
+            .iter()
+            .all(|class| password.chars().any(|c| class.contains(c)))
+    }
+}
+
+fn generate_os(policy: &Policy) -> Result<Secret<String>, Error> {
+    let alphabet = policy.alphabet();
+    let limit = 256 - 256 % alphabet.len();
+    let mut bytes = Secret::new([0u8; 64]);
+
+    loop {
+        let mut password = Secret::new(String::with_capacity(policy.length));
+        while password.len() < policy.length {
+            getrandom::getrandom(&mut *bytes).map_err(Error::Random)?;
+            for byte in bytes.iter().map(|byte| *byte as usize) {
+                if byte < limit && password.len() < policy.length {
+                    password.push(alphabet[byte % alphabet.len()]);
+                }
+            }
+        }
+
+        if policy.accepts(&password) {
+            return Ok(password);
+        }
+
+        info!("Generated password does not satisfy the policy (skip)");
+    }
+}
+
+fn divmod(number: &mut [u8], divisor: usize) -> usize {
+    let mut remainder = 0;
+    for byte in number.iter_mut() {
+        let value = remainder * 256 + *byte as usize;
+        *byte = (value / divisor) as u8;
+        remainder = value % divisor;
+    }
+    remainder
+}
+
+pub fn generate_derived(policy: &Policy, entropy: &mut [u8]) -> Secret<String> {
+    let classes: Vec<Vec<char>> = policy
+        .classes()
+        .iter()
+        .map(|class| class.chars().collect())
+        .collect();
+    let alphabet: Vec<char> = classes.concat();
+
+    let mut password = Secret::new(Vec::with_capacity(policy.length));
+    while password.len() < policy.length.saturating_sub(classes.len()) {
+        password.push(alphabet[divmod(entropy, alphabet.len())]);
+    }
+    let required: Vec<char> = classes
+        .iter()
+        .map(|class| class[divmod(entropy, class.len())])
+        .collect();
+    for c in required {
+        let i = divmod(entropy, password.len().max(1));
+        password.insert(i, c);
+    }
+
+    Secret::new(password.iter().collect())
+}
+
+pub struct Generator {
+    source: Source,
+    pwgen: PathBuf,
+    pwgen_args: Option<Vec<String>>,
+}
+
+impl Generator {
+    pub fn new(source: Source, pwgen: Option<PathBuf>, pwgen_args: Option<Vec<String>>) -> Self {
+        Generator {
+            source,
+            pwgen: pwgen.unwrap_or_else(|| PathBuf::from("pwgen")),
+            pwgen_args,
+        }
+    }
+
+    pub fn generate(&self, policy: &Policy) -> Result<Secret<String>, Error> {
+        let password = match self.source {
+            Source::Pwgen => self.generate_pwgen(policy)?,
+            Source::Os => generate_os(policy)?,
+        };
+        info!("Entropy: {:.1} bits", self.entropy(policy, &password));
+        Ok(password)
+    }
+
+    pub fn entropy(&self, policy: &Policy, password: &str) -> f64 {
+        let alphabet = match (self.source, &self.pwgen_args) {
+            (Source::Pwgen, Some(_)) => [LOWERCASE, UPPERCASE, DIGITS, SYMBOLS]
+                .iter()
+                .filter(|class| password.chars().any(|c| class.contains(c)))
+                .map(|class| class.len())
+                .sum(),
+            _ => policy.alphabet().len(),
+        };
+        password.chars().count() as f64 * (alphabet as f64).log2()
+    }
+
+    fn generate_pwgen(&self, policy: &Policy) -> Result<Secret<String>, Error> {
+        let args = match (&self.pwgen_args, &policy.name) {
+            (Some(_), Some(name)) => return Err(Error::PwGenArgsPolicy(name.clone())),
+            (Some(args), None) => args.clone(),
+            (None, _) => policy.pwgen_args(),
+        };
+
+        match generate_pwgen(&self.pwgen, &args) {
+            Err(Error::PwGenSpawn(e)) if e.kind() == io::ErrorKind::NotFound => {
+                warn!(
+                    "Could not find {}, using the internal generator",
+                    self.pwgen.display()
+                );
+                generate_os(policy)
+            }
+            result => result,
+        }
+    }
+}
+
+fn generate_pwgen(pwgen: &Path, args: &[String]) -> Result<Secret<String>, Error> {
     'gen_loop: loop {
-        let mut child = process::Command::new("pwgen")
-            .args(&["-c", "-n", "-y", "-s", "-B", "-1", "34", "1"])
+        let mut child = process::Command::new(pwgen)
+            .args(args)
             .stdin(process::Stdio::null())
             .stdout(process::Stdio::piped())
             .stderr(process::Stdio::piped())
@@ -38,25 +296,30 @@ pub fn generate() -> Result<(), Error> {
         }
 
         if let Some(mut out) = child.stdout {
-            let mut out_str = String::new();
+            let mut out_str = Secret::new(String::new());
 
             if let Err(e) = out.read_to_string(&mut out_str) {
                 return Err(Error::PwGenStdoutErr(e));
             } else {
-                let out_str = out_str.trim().to_string();
+                let out_str = Secret::new(out_str.trim().to_string());
 
                 if let Some(c) = out_str.chars().next() {
                     if c.is_ascii_punctuation() {
-                        info!("Password ({}) starts with a symbol (skip)", out_str);
+                        info!(
+                            "Password ({}) starts with a symbol (skip)",
+                            redacted(&out_str)
+                        );
                         continue 'gen_loop;
                     } else {
                         if let Some(c) = out_str.chars().last() {
                             if c.is_ascii_punctuation() {
-                                info!("Password ({}) ends with a symbol (skip)", out_str);
+                                info!(
+                                    "Password ({}) ends with a symbol (skip)",
+                                    redacted(&out_str)
+                                );
                                 continue 'gen_loop;
                             } else {
-                                println!("{}", out_str);
-                                break 'gen_loop;
+                                return Ok(out_str);
                             }
                         }
                     }
@@ -68,6 +331,46 @@ pub fn generate() -> Result<(), Error> {
             return Err(Error::PwGenNoStdout);
         }
     }
+}
+
+const LINE_WIDTH: usize = 80;
+
+pub fn output(
+    clip: Option<Selection>,
+    count: Option<usize>,
+    policy: &Policy,
+    generator: &Generator,
+    verbose: bool,
+) -> Result<(), Error> {
+    let entropy = |password: &str| {
+        if verbose {
+            eprintln!("Entropy: {:.1} bits", generator.entropy(policy, password));
+        }
+    };
+
+    if let Some(count) = count {
+        let mut passwords = Vec::with_capacity(count);
+        for _ in 0..count {
+            let password = generator.generate(policy)?;
+            entropy(&password);
+            passwords.push(password);
+        }
+
+        let columns = (LINE_WIDTH / (policy.length + 1)).max(1);
+        for (i, password) in passwords.iter().enumerate() {
+            let last_column = (i + 1) % columns == 0 || i + 1 == passwords.len();
+            print!("{}{}", **password, if last_column { '\n' } else { ' ' });
+        }
+
+        return Ok(());
+    }
 
+    let password = generator.generate(policy)?;
+    entropy(&password);
+    if let Some(selection) = clip {
+        clip::copy(&password, selection)?;
+    } else {
+        println!("{}", *password);
+    }
     Ok(())
 }
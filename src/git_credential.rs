@@ -0,0 +1,98 @@
+use crate::add::append_entry;
+use crate::audit;
+use crate::cmdline::Credential;
+use crate::config::Config;
+use crate::err::Error;
+use crate::file;
+use crate::link;
+use crate::rm;
+use crate::{check_value, find_unique, parse, read, EntryData, Secret, VALID};
+use log::info;
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+
+type Request = HashMap<String, Secret<String>>;
+
+fn read_request() -> Result<Request, Error> {
+    let mut request = HashMap::new();
+    for line in io::stdin().lock().lines() {
+        let line = Secret::new(line.map_err(Error::CredentialRead)?);
+        if line.is_empty() {
+            break;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            request.insert(key.to_string(), Secret::new(value.to_string()));
+        }
+    }
+    Ok(request)
+}
+
+fn get<'a>(request: &'a Request, key: &str) -> Option<&'a str> {
+    request.get(key).map(|value| value.as_str())
+}
+
+fn host(request: &Request) -> Option<&str> {
+    let host = get(request, "host")?;
+    let host = host.split(':').next().unwrap_or(host);
+    Some(host.trim_start_matches("www."))
+}
+
+fn matches(entry: &EntryData, host: &str, username: Option<&str>) -> bool {
+    link::host(entry.link).as_deref() == Some(host)
+        && username.is_none_or(|username| entry.username == username)
+}
+
+pub fn git_credential(file: PathBuf, action: Credential, config: &Config) -> Result<(), Error> {
+    let request = read_request()?;
+    let host = match host(&request) {
+        Some(host) => host,
+        None => return Ok(()),
+    };
+    let username = get(&request, "username");
+
+    let data = read(&file)?;
+    let matched = find_unique(&data, VALID, host, |entry| matches(entry, host, username))?;
+
+    match (action, matched) {
+        (Credential::Get, Some(entry)) => {
+            audit::record(config, "git-credential", &[entry.name])?;
+            println!("username={}", entry.username);
+            println!("password={}", entry.password);
+        }
+        (Credential::Get, None) => {}
+        (Credential::Store, matched) => {
+            let (username, password) = match (username, get(&request, "password")) {
+                (Some(username), Some(password)) => (username, password),
+                _ => return Ok(()),
+            };
+            check_value("username", username)?;
+            check_value("password", password)?;
+            let edited = match matched {
+                Some(entry) if entry.password == password => return Ok(()),
+                Some(entry) => file::replace_line(&data, entry.line, |line| {
+                    file::replace_token(line, 4, password)
+                }),
+                None => {
+                    let taken = parse(&data)
+                        .filter_map(Result::ok)
+                        .any(|entry| entry.data().named(host));
+                    let name = if taken {
+                        format!("{}/{}", host, username)
+                    } else {
+                        host.to_string()
+                    };
+                    let protocol = get(&request, "protocol").unwrap_or("https");
+                    let link = format!("{}://{}", protocol, host);
+                    append_entry(&data, &[&name, &link, username, password])
+                }
+            };
+            file::write(&file, &edited)?;
+            info!("Stored the git credentials for {}", host);
+        }
+        (Credential::Erase, Some(entry)) => rm::remove(file.clone(), entry.name.to_string())?,
+        (Credential::Erase, None) => {}
+    }
+
+    Ok(())
+}
@@ -0,0 +1,338 @@
+use crate::cmdline::{Import, OnConflict};
+use crate::config::Config;
+use crate::err::Error;
+use crate::file;
+use crate::otp;
+use crate::prompt;
+use crate::{parse, read, Entry, EntryData, Secret, MASK};
+use log::{info, warn};
+use serde_json::Value;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const SAMPLES: usize = 5;
+
+struct Token {
+    issuer: String,
+    label: String,
+    secret: Secret<String>,
+    params: Vec<(&'static str, String)>,
+}
+
+fn token(
+    issuer: &Value,
+    label: &Value,
+    secret: &Value,
+    params: [&Value; 3],
+) -> Result<Token, Error> {
+    let text = |value: &Value| value.as_str().unwrap_or_default().trim().to_string();
+    let [digits, period, algorithm] = params;
+    Ok(Token {
+        issuer: text(issuer),
+        label: text(label),
+        secret: otp::normalize(secret.as_str().unwrap_or_default())?,
+        params: otp::params(vec![
+            digits.as_u64().map(|n| n.to_string()),
+            period.as_u64().map(|n| n.to_string()),
+            algorithm.as_str().map(str::to_uppercase),
+        ]),
+    })
+}
+
+fn fields(n: u32, token: &Token) -> String {
+    otp::fields(n, &token.secret, &token.params)
+}
+
+fn describe(token: &Token) -> String {
+    match (token.issuer.as_str(), token.label.as_str()) {
+        ("", label) => label.to_string(),
+        (issuer, "") => issuer.to_string(),
+        (issuer, label) => format!("{} {}", issuer, label),
+    }
+}
+
+fn tokens(backup: &Value) -> Result<Vec<Token>, Error> {
+    if let Some(entries) = backup.as_array() {
+        return entries
+            .iter()
+            .filter(|entry| entry["type"].as_str() == Some("TOTP"))
+            .map(|entry| {
+                let params = [&entry["digits"], &entry["period"], &entry["algorithm"]];
+                token(&entry["issuer"], &entry["label"], &entry["secret"], params)
+            })
+            .collect();
+    }
+
+    match &backup["db"] {
+        Value::String(_) => Err(Error::OathFormat(
+            "encrypted Aegis vaults are not supported",
+        )),
+        db => match db["entries"].as_array() {
+            Some(entries) => entries
+                .iter()
+                .filter(|entry| entry["type"].as_str() == Some("totp"))
+                .map(|entry| {
+                    let info = &entry["info"];
+                    let params = [&info["digits"], &info["period"], &info["algo"]];
+                    token(&entry["issuer"], &entry["name"], &info["secret"], params)
+                })
+                .collect(),
+            None => Err(Error::OathFormat("expected an andOTP or Aegis export")),
+        },
+    }
+}
+
+fn named(entry: &EntryData, key: &str) -> bool {
+    Some(entry.name)
+        .into_iter()
+        .chain(entry.aliases())
+        .any(|name| name.eq_ignore_ascii_case(key))
+}
+
+fn find<'a>(entries: &[EntryData<'a>], token: &Token) -> Option<EntryData<'a>> {
+    let prefix = token.label.split_once(':').map(|(issuer, _)| issuer);
+    for key in [
+        Some(token.issuer.as_str()),
+        Some(token.label.as_str()),
+        prefix,
+    ]
+    .iter()
+    .flatten()
+    .filter(|key| !key.is_empty())
+    {
+        let mut matched = entries.iter().filter(|entry| named(entry, key));
+        match (matched.next(), matched.next()) {
+            (Some(entry), None) => return Some(entry.clone()),
+            (Some(_), Some(_)) => {
+                warn!("Several accounts match {} (skip)", key);
+                return None;
+            }
+            _ => {}
+        }
+    }
+    warn!("No account matches {} (skip)", describe(token));
+    None
+}
+
+fn secrets(line: &str) -> impl Iterator<Item = (usize, u32, &str)> {
+    line.split_whitespace()
+        .enumerate()
+        .skip(5)
+        .filter_map(|(index, token)| {
+            let (key, value) = token.split_once('=')?;
+            let n = match key.strip_prefix("otp")? {
+                "" => 1,
+                n => n.parse().ok()?,
+            };
+            Some((index, n, value))
+        })
+}
+
+fn merge(line: &str, token: &Token, on_conflict: OnConflict) -> Option<String> {
+    let primary = secrets(line).find(|(_, n, _)| *n == 1);
+    match (primary, on_conflict) {
+        (None, _) => Some(format!("{} {}", line.trim_end(), fields(1, token))),
+        (Some(_), OnConflict::Skip) | (Some(_), OnConflict::Ask) => None,
+        (Some(_), OnConflict::Overwrite) => {
+            let line = otp::param_tokens(line)
+                .into_iter()
+                .rev()
+                .fold(line.to_string(), |line, index| {
+                    file::remove_token(&line, index)
+                });
+            let (index, _, _) = secrets(&line).find(|(_, n, _)| *n == 1)?;
+            Some(file::replace_token(&line, index, &fields(1, token)))
+        }
+        (Some(_), OnConflict::Rename) => {
+            let n = secrets(line).map(|(_, n, _)| n).max().unwrap_or(1) + 1;
+            Some(format!("{} {}", line.trim_end(), fields(n, token)))
+        }
+    }
+}
+
+fn hint(secret: &str) -> String {
+    format!("{}{}", secret.chars().take(4).collect::<String>(), MASK)
+}
+
+fn ask(name: &str, mine: &str, token: &Token, timeout: Duration) -> Result<OnConflict, Error> {
+    eprintln!("{} already has another OTP secret", name);
+    eprintln!("  {:9} {:24} theirs", "", "mine");
+    eprintln!("  {:9} {:24} {}", "Account:", name, describe(token));
+    eprintln!("  {:9} {:24} {}", "OTP:", hint(mine), hint(&token.secret));
+
+    loop {
+        let answer = match prompt::read("Keep [m]ine, [t]heirs or [b]oth? ", false, timeout)? {
+            Some(answer) => answer,
+            None => return Ok(OnConflict::Skip),
+        };
+        match answer.trim() {
+            "m" | "mine" => return Ok(OnConflict::Skip),
+            "t" | "theirs" => return Ok(OnConflict::Overwrite),
+            "b" | "both" => return Ok(OnConflict::Rename),
+            _ => {}
+        }
+    }
+}
+
+fn oath(
+    file: &Path,
+    backup: &Path,
+    on_conflict: OnConflict,
+    timeout: Duration,
+) -> Result<(), Error> {
+    if on_conflict == OnConflict::Ask && !io::stdin().is_terminal() {
+        return Err(Error::NotInteractive);
+    }
+
+    let backup = Secret::new(fs::read_to_string(backup).map_err(Error::ImportRead)?);
+    let backup: Value = serde_json::from_str(&backup).map_err(Error::OathJson)?;
+    let tokens = tokens(&backup)?;
+
+    let data = read(file)?;
+    let mut entries = Vec::new();
+    for entry in parse(&data) {
+        if let Entry::Valid(entry) = entry? {
+            entries.push(entry);
+        }
+    }
+
+    let mut edited = Secret::new(data.to_string());
+    let (mut new, mut conflicting, mut skipped) = (0, 0, 0);
+    let mut imported = Vec::new();
+    for token in &tokens {
+        let entry = match find(&entries, token) {
+            Some(entry) => entry,
+            None => {
+                skipped += 1;
+                continue;
+            }
+        };
+        let line = edited.lines().nth(entry.line - 1).unwrap_or_default();
+        if secrets(line).any(|(_, _, secret)| secret == token.secret.as_str()) {
+            info!("{} already has this OTP secret", entry.name);
+            skipped += 1;
+            continue;
+        }
+        let strategy = match secrets(line).find(|(_, n, _)| *n == 1) {
+            Some((_, _, mine)) => {
+                conflicting += 1;
+                match on_conflict {
+                    OnConflict::Ask => ask(entry.name, mine, token, timeout)?,
+                    strategy => strategy,
+                }
+            }
+            None => {
+                new += 1;
+                on_conflict
+            }
+        };
+        let merged = match merge(line, token, strategy) {
+            Some(merged) => Secret::new(merged),
+            None => {
+                warn!("{} already has another OTP secret (skip)", entry.name);
+                continue;
+            }
+        };
+        edited = file::replace_line(&edited, entry.line, |_| merged.to_string());
+        imported.push(format!("{} ({})", entry.name, describe(token)));
+    }
+
+    if file::dry_run() {
+        println!(
+            "{} new, {} conflicting, {} skipped of {} OTP secrets",
+            new,
+            conflicting,
+            skipped,
+            tokens.len()
+        );
+        for name in imported.iter().take(SAMPLES) {
+            println!("  {} otp={}", name, MASK);
+        }
+        if imported.len() > SAMPLES {
+            println!("  ... and {} more", imported.len() - SAMPLES);
+        }
+    }
+
+    if !imported.is_empty() {
+        file::write(file, &edited)?;
+    }
+    info!(
+        "Imported {} of {} OTP secrets",
+        imported.len(),
+        tokens.len()
+    );
+
+    Ok(())
+}
+
+pub fn import(file: PathBuf, command: Import, config: &Config) -> Result<(), Error> {
+    match command {
+        Import::Oath {
+            backup,
+            on_conflict,
+            ..
+        } => oath(&file, &backup, on_conflict, config.prompt_timeout()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{merge, Token};
+    use crate::cmdline::OnConflict;
+    use crate::Secret;
+
+    const LINE: &str = "+ github https://github.com me hunter2 otp=AAAA otp-digits=8";
+
+    fn token(params: Vec<(&'static str, String)>) -> Token {
+        Token {
+            issuer: String::from("GitHub"),
+            label: String::from("me"),
+            secret: Secret::new(String::from("BBBB")),
+            params,
+        }
+    }
+
+    #[test]
+    fn overwrite() {
+        let token = token(vec![("period", String::from("60"))]);
+        assert_eq!(
+            merge(LINE, &token, OnConflict::Overwrite).as_deref(),
+            Some("+ github https://github.com me hunter2 otp=BBBB otp-period=60")
+        );
+    }
+
+    #[test]
+    fn rename() {
+        let token = token(vec![("digits", String::from("7"))]);
+        assert_eq!(
+            merge(LINE, &token, OnConflict::Rename).as_deref(),
+            Some(
+                "+ github https://github.com me hunter2 otp=AAAA otp-digits=8 \
+                 otp2=BBBB otp2-digits=7"
+            )
+        );
+        assert_eq!(merge(LINE, &token, OnConflict::Skip), None);
+    }
+
+    #[test]
+    fn params() {
+        let token = token(vec![
+            ("digits", String::from("8")),
+            ("algorithm", String::from("SHA256")),
+        ]);
+        assert_eq!(
+            merge(
+                "+ github https://github.com me hunter2 tags=work",
+                &token,
+                OnConflict::Skip
+            )
+            .as_deref(),
+            Some(
+                "+ github https://github.com me hunter2 tags=work \
+                 otp=BBBB otp-digits=8 otp-algorithm=SHA256"
+            )
+        );
+    }
+}
@@ -0,0 +1,1007 @@
+#![warn(clippy::all)]
+
+mod add;
+mod askpass;
+mod attach;
+mod audit;
+mod check;
+mod clip;
+mod cmdline;
+mod complete;
+mod compress;
+mod config;
+mod derive;
+mod docker_credential;
+mod err;
+mod exec;
+mod export;
+mod file;
+mod gen;
+mod git_credential;
+mod import;
+mod link;
+mod logger;
+mod mark;
+mod match_url;
+mod mv;
+mod notify;
+mod open;
+mod otp;
+mod prompt;
+mod recovery;
+mod render;
+mod report;
+mod rm;
+mod rotate;
+mod share;
+mod show;
+mod sign;
+mod split;
+mod stats;
+mod sudo_askpass;
+#[cfg(unix)]
+mod systemd_agent;
+mod tree;
+mod undo;
+mod verify;
+#[cfg(all(unix, not(target_os = "macos")))]
+mod wayland;
+mod wifi_qr;
+
+use add::add;
+use askpass::askpass;
+use attach::attach;
+use check::{check, Checker};
+use cmdline::{Cmd, Field, LogFormat, Mark, Pw, Stats};
+use compress::compress;
+use config::{Config, Marker};
+use derive::derive;
+use docker_credential::docker_credential;
+pub use err::Error;
+use exec::exec;
+use export::export;
+use file::{get_passfile, with_suffix};
+use git_credential::git_credential;
+use import::import;
+use log::{info, log_enabled, trace};
+use mark::mark;
+use match_url::match_url;
+use mv::rename;
+use notify::notify;
+use open::open;
+use otp::otp;
+use recovery::recovery;
+use render::render;
+use report::report;
+use rm::remove;
+use rotate::rotate;
+use serde_json::json;
+use share::share;
+use show::show;
+use sign::sign;
+use split::split;
+use stats::{stats, usernames};
+use std::cell::RefCell;
+use std::fmt::Debug;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+use std::str;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use structopt::StructOpt;
+use sudo_askpass::sudo_askpass;
+#[cfg(unix)]
+use systemd_agent::systemd_agent;
+use tree::Tree;
+use undo::undo;
+use verify::verify;
+use wifi_qr::wifi_qr;
+use zeroize::Zeroizing;
+
+type Secret<T> = Zeroizing<T>;
+
+static MARKERS: OnceLock<Vec<Marker>> = OnceLock::new();
+static QUIET: AtomicBool = AtomicBool::new(false);
+static REDACT: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+fn redacted(secret: &str) -> &str {
+    if REDACT.load(Ordering::Relaxed) {
+        MASK
+    } else {
+        secret
+    }
+}
+
+fn custom_marker(symbol: &str) -> Option<&'static Marker> {
+    MARKERS.get()?.iter().find(|marker| marker.symbol == symbol)
+}
+
+const MASK: &str = "********";
+
+pub const VALID: &[Mark] = &[Mark::Valid];
+const ACTIVE: &[Mark] = &[Mark::Valid, Mark::Change];
+const ANY: &[Mark] = &[Mark::Valid, Mark::Invalid, Mark::Change];
+
+fn reveal(reveal: bool) -> bool {
+    reveal || !io::stdout().is_terminal()
+}
+
+fn fmt_entry(fmt: &str, entry: &EntryData, reveal: bool) -> String {
+    let mut iter = fmt.chars();
+    let mut out = String::new();
+    while let Some(c) = iter.next() {
+        match c {
+            '%' => match iter.next() {
+                Some('N') => out.push_str(entry.name),
+                Some('L') => out.push_str(entry.link),
+                Some('U') => out.push_str(entry.username),
+                Some('P') if reveal => out.push_str(entry.password),
+                Some('P') => out.push_str(MASK),
+                Some(c2) => {
+                    out.push(c);
+                    out.push(c2);
+                }
+                None => {
+                    out.push(c);
+                    break;
+                }
+            },
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[derive(Debug, Clone)]
+pub struct EntryData<'a> {
+    line: usize,
+    marker: &'a str,
+    name: &'a str,
+    link: &'a str,
+    username: &'a str,
+    password: &'a str,
+    fields: &'a str,
+}
+
+impl<'a> EntryData<'a> {
+    fn parse(
+        num: usize,
+        line: &'a str,
+        marker: &'a str,
+        mut iter: impl Iterator<Item = (usize, &'a str)>,
+    ) -> Result<Self, Error> {
+        let mut next = |missing: fn(usize, usize) -> Error| {
+            iter.next()
+                .map(|(_, token)| token)
+                .ok_or_else(|| missing(num, column(line, line.trim_end().len()) + 1))
+        };
+        let name = next(Error::MissingName)?;
+        let link = next(Error::MissingLink)?;
+        let username = next(Error::MissingUsername)?;
+        let password = next(Error::MissingPassword)?;
+        let mut fields = "";
+        for (offset, field) in iter {
+            if !field.contains('=') {
+                return Err(Error::InvalidField(num, column(line, offset)));
+            }
+            if fields.is_empty() {
+                fields = &line[offset..];
+            }
+        }
+        Ok(EntryData {
+            line: num,
+            marker,
+            name,
+            link,
+            username,
+            password,
+            fields,
+        })
+    }
+
+    fn fields(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        self.fields
+            .split_whitespace()
+            .filter_map(|field| field.split_once('='))
+    }
+
+    fn field(&self, key: &str) -> Option<&'a str> {
+        self.fields()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    fn tags(&self) -> impl Iterator<Item = &'a str> {
+        self.field("tags")
+            .into_iter()
+            .flat_map(|tags| tags.split(','))
+            .filter(|tag| !tag.is_empty())
+    }
+
+    fn aliases(&self) -> impl Iterator<Item = &'a str> {
+        self.field("aliases")
+            .into_iter()
+            .flat_map(|aliases| aliases.split(','))
+            .filter(|alias| !alias.is_empty())
+    }
+
+    fn outdated(&self, cutoff: Option<&str>) -> bool {
+        match (self.field("changed"), cutoff) {
+            (Some(changed), Some(cutoff)) => changed < cutoff,
+            _ => false,
+        }
+    }
+
+    fn answer(&self, question: u32) -> Option<&'a str> {
+        self.field(&format!("a{}", question))
+    }
+
+    fn named(&self, name: &str) -> bool {
+        self.name == name || self.aliases().any(|alias| alias == name)
+    }
+}
+
+#[derive(Clone)]
+pub enum Entry<'a> {
+    Valid(EntryData<'a>),
+    Invalid(EntryData<'a>),
+    Change(EntryData<'a>),
+}
+
+impl<'a> Entry<'a> {
+    fn parse(num: usize, line: &'a str) -> Result<Self, Error> {
+        if let Some((offset, c)) = control_char(line) {
+            return Err(Error::ControlChar(num, column(line, offset), c));
+        }
+        let mut iter = tokens(line);
+        let (offset, marker) = iter.next().ok_or(Error::MissingMarker(num, 1))?;
+        let data = EntryData::parse(num, line, marker, iter)?;
+        let mark = parse_mark(marker).ok_or_else(|| {
+            Error::InvalidEntryMarker(num, column(line, offset), marker.to_string())
+        })?;
+        if log_enabled!(log::Level::Trace) {
+            trace_entry(num, line, marker, mark);
+        }
+        Ok(match mark {
+            Mark::Valid => Entry::Valid(data),
+            Mark::Invalid => Entry::Invalid(data),
+            Mark::Change => Entry::Change(data),
+        })
+    }
+
+    fn mark(&self) -> Mark {
+        match self {
+            Entry::Valid(_) => Mark::Valid,
+            Entry::Invalid(_) => Mark::Invalid,
+            Entry::Change(_) => Mark::Change,
+        }
+    }
+
+    fn data(&self) -> &EntryData<'a> {
+        match self {
+            Entry::Valid(data) | Entry::Invalid(data) | Entry::Change(data) => data,
+        }
+    }
+
+    fn into_data(self) -> EntryData<'a> {
+        match self {
+            Entry::Valid(data) | Entry::Invalid(data) | Entry::Change(data) => data,
+        }
+    }
+}
+
+fn parse_mark(marker: &str) -> Option<Mark> {
+    match marker {
+        "+" => Some(Mark::Valid),
+        "-" => Some(Mark::Invalid),
+        "*" => Some(Mark::Change),
+        _ => custom_marker(marker).map(|custom| custom.mark),
+    }
+}
+
+fn trace_entry(num: usize, line: &str, marker: &str, mark: Mark) {
+    const LABELS: [&str; 4] = ["name", "link", "username", "password"];
+    let tokens: Vec<String> = tokens(line)
+        .skip(1)
+        .enumerate()
+        .map(|(i, (offset, token))| (i, column(line, offset), token))
+        .map(
+            |(i, column, token)| match (LABELS.get(i), token.split_once('=')) {
+                (Some(&"password"), _) => format!("password@{} {}", column, MASK),
+                (Some(label), _) => format!("{}@{} {:?}", label, column, token),
+                (None, Some((key, _))) if check::secret_field(key) => {
+                    format!("field@{} {}={}", column, key, MASK)
+                }
+                (None, _) => format!("field@{} {:?}", column, token),
+            },
+        )
+        .collect();
+    trace!(
+        "Line {}: marker {:?} is {:?}, {}",
+        num,
+        marker,
+        mark,
+        tokens.join(", ")
+    );
+}
+
+fn control_char(line: &str) -> Option<(usize, char)> {
+    let start = line
+        .bytes()
+        .position(|b| (b < 0x20 && b != b'\t') || b >= 0x7f)?;
+    line[start..]
+        .char_indices()
+        .map(|(offset, c)| (start + offset, c))
+        .find(|&(_, c)| (c.is_control() && c != '\t') || c == '\u{feff}')
+}
+
+fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace()
+        .map(move |token| (token.as_ptr() as usize - line.as_ptr() as usize, token))
+}
+
+fn check_value(what: &'static str, value: &str) -> Result<(), Error> {
+    if value.is_empty() || value.contains(char::is_whitespace) {
+        return Err(Error::InvalidValue(what, value.to_string()));
+    }
+    Ok(())
+}
+
+fn parse_line(num: usize, line: &str) -> Option<Result<Entry<'_>, Error>> {
+    let trimmed = line.trim();
+    if trimmed.is_empty() {
+        trace!("Line {}: blank (skip)", num);
+        None
+    } else if trimmed.starts_with('#') {
+        trace!("Line {}: comment (skip)", num);
+        None
+    } else {
+        Some(Entry::parse(num, line))
+    }
+}
+
+pub fn parse(data: &str) -> impl Iterator<Item = Result<Entry<'_>, Error>> {
+    data.lines()
+        .enumerate()
+        .filter_map(|(num, line)| parse_line(num + 1, line))
+}
+
+fn scan(file: &Path, mut visit: impl FnMut(Entry) -> Result<(), Error>) -> Result<(), Error> {
+    file::scan(file, |num, line| match parse_line(num, line) {
+        Some(entry) => visit(entry?),
+        None => Ok(()),
+    })
+}
+
+fn location(prefix: &str) -> (usize, usize) {
+    let start = prefix.rfind('\n').map_or(0, |i| i + 1);
+    (
+        prefix.matches('\n').count() + 1,
+        column(prefix, prefix.len()) - column(prefix, start) + 1,
+    )
+}
+
+fn read<P: AsRef<Path>>(file: P) -> Result<Secret<String>, Error> {
+    let mut bytes = file::load(file.as_ref())?;
+    match String::from_utf8(std::mem::take(&mut *bytes)) {
+        Ok(data) => Ok(Secret::new(data)),
+        Err(e) => {
+            let valid_up_to = e.utf8_error().valid_up_to();
+            let bytes = Secret::new(e.into_bytes());
+            let valid = String::from_utf8_lossy(&bytes[..valid_up_to]);
+            let (line, column) = location(&valid);
+            Err(Error::InvalidUtf8(line, column))
+        }
+    }
+}
+
+trait Visitor<'a> {
+    fn line(&mut self, _num: usize, _line: &'a str) {}
+
+    fn entry(&mut self, entry: Entry<'a>) -> Result<(), Error>;
+
+    fn error(&mut self, e: Error) -> Result<(), Error> {
+        Err(e)
+    }
+}
+
+impl<'a, A: Visitor<'a>, B: Visitor<'a>> Visitor<'a> for (A, B) {
+    fn line(&mut self, num: usize, line: &'a str) {
+        self.0.line(num, line);
+        self.1.line(num, line);
+    }
+
+    fn entry(&mut self, entry: Entry<'a>) -> Result<(), Error> {
+        self.0.entry(entry.clone())?;
+        self.1.entry(entry)
+    }
+
+    fn error(&mut self, e: Error) -> Result<(), Error> {
+        self.0.error(e)
+    }
+}
+
+fn visit<'a>(data: &'a str, visitor: &mut impl Visitor<'a>) -> Result<(), Error> {
+    for (num, line) in data.lines().enumerate() {
+        match parse_line(num + 1, line) {
+            Some(Ok(entry)) => visitor.entry(entry)?,
+            Some(Err(e)) => visitor.error(e)?,
+            None => {}
+        }
+        visitor.line(num + 1, line);
+    }
+    Ok(())
+}
+
+struct Finder<'q, 'a, F> {
+    states: &'q [Mark],
+    queries: &'q [&'q str],
+    matches: F,
+    matched: Vec<Option<EntryData<'a>>>,
+}
+
+impl<'q, 'a, F: Fn(&EntryData<'a>, usize) -> bool> Finder<'q, 'a, F> {
+    fn new(states: &'q [Mark], queries: &'q [&'q str], matches: F) -> Self {
+        Finder {
+            states,
+            queries,
+            matches,
+            matched: vec![None; queries.len()],
+        }
+    }
+
+    fn finish(self) -> Result<Vec<EntryData<'a>>, Error> {
+        self.matched
+            .into_iter()
+            .zip(self.queries)
+            .map(|(entry, query)| entry.ok_or_else(|| Error::NoMatches(query.to_string())))
+            .collect()
+    }
+}
+
+impl<'a, F: Fn(&EntryData<'a>, usize) -> bool> Visitor<'a> for Finder<'_, 'a, F> {
+    fn entry(&mut self, entry: Entry<'a>) -> Result<(), Error> {
+        if !self.states.contains(&entry.mark()) {
+            return Ok(());
+        }
+        let data = entry.into_data();
+        for (i, query) in self.queries.iter().enumerate() {
+            if (self.matches)(&data, i) {
+                if let Some(first) = &self.matched[i] {
+                    return Err(Error::Mismatch(query.to_string(), first.line, data.line));
+                }
+                self.matched[i] = Some(data.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn find_all<'a>(
+    data: &'a str,
+    states: &[Mark],
+    queries: &[&str],
+    matches: impl Fn(&EntryData<'a>, usize) -> bool,
+) -> Result<Vec<EntryData<'a>>, Error> {
+    let mut finder = Finder::new(states, queries, matches);
+    visit(data, &mut finder)?;
+    finder.finish()
+}
+
+fn find_unique<'a>(
+    data: &'a str,
+    states: &[Mark],
+    query: &str,
+    matches: impl Fn(&EntryData<'a>) -> bool,
+) -> Result<Option<EntryData<'a>>, Error> {
+    match find_all(data, states, &[query], |entry, _| matches(entry)) {
+        Ok(mut entries) => Ok(entries.pop()),
+        Err(Error::NoMatches(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+pub fn find<'a>(data: &'a str, states: &[Mark], acc: &str) -> Result<EntryData<'a>, Error> {
+    let mut entries = find_all(data, states, &[acc], |entry, _| entry.named(acc))?;
+    Ok(entries.remove(0))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn get(
+    file: PathBuf,
+    accs: Vec<String>,
+    format: Option<String>,
+    fields: Vec<Field>,
+    url: bool,
+    strict: bool,
+    no_newline: bool,
+    reveal: bool,
+    config: &Config,
+) -> Result<(), Error> {
+    let queries: Vec<&str> = accs.iter().map(String::as_str).collect();
+    let domains = if url {
+        queries
+            .iter()
+            .map(|url| link::domain(url).ok_or_else(|| Error::InvalidUrl(url.to_string())))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        Vec::new()
+    };
+
+    let reveal = self::reveal(reveal);
+    let data = read(file)?;
+    let matches = |entry: &EntryData, i: usize| {
+        if url {
+            link::domain(entry.link).as_ref() == Some(&domains[i])
+        } else {
+            entry.named(queries[i])
+        }
+    };
+    let entries = if strict {
+        let mut visitor = (Checker::default(), Finder::new(VALID, &queries, matches));
+        visit(&data, &mut visitor)?;
+        let (checker, finder) = visitor;
+        checker.finish(&data, true, false)?;
+        finder.finish()?
+    } else {
+        find_all(&data, VALID, &queries, matches)?
+    };
+    let mut out = Secret::new(Vec::new());
+    for entry in &entries {
+        if let Some(format) = &format {
+            out.push(fmt_entry(format, entry, reveal));
+        }
+        for field in &fields {
+            out.push(match *field {
+                Field::Answer(question) => {
+                    let answer = entry
+                        .answer(question)
+                        .ok_or_else(|| Error::NoAnswer(entry.name.to_string(), question))?;
+                    String::from(if reveal { answer } else { MASK })
+                }
+                field => fmt_entry(field.format().unwrap_or_default(), entry, reveal),
+            });
+        }
+    }
+
+    let names: Vec<&str> = entries.iter().map(|entry| entry.name).collect();
+    audit::record(config, "get", &names)?;
+
+    let out = Secret::new(out.join("\n"));
+    if no_newline {
+        print!("{}", *out);
+    } else {
+        println!("{}", *out);
+    }
+    Ok(())
+}
+
+fn matcher(query: &str) -> impl Fn(&str) -> bool {
+    let query = query.to_lowercase();
+    let folded = RefCell::new(String::new());
+    move |name: &str| {
+        let mut folded = folded.borrow_mut();
+        folded.clear();
+        folded.extend(name.chars().flat_map(char::to_lowercase));
+        if query.ends_with('/') {
+            folded.starts_with(&query)
+        } else {
+            folded.contains(&query)
+        }
+    }
+}
+
+fn rank(entry: &EntryData, matches: impl Fn(&str) -> bool) -> Option<usize> {
+    if matches(entry.name) {
+        Some(0)
+    } else if entry.aliases().any(&matches) {
+        Some(1)
+    } else if link::host(entry.link).is_some_and(|host| matches(&host)) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn list(
+    file: PathBuf,
+    query: String,
+    archived: bool,
+    reveal: bool,
+    tree: bool,
+    jsonl: bool,
+    null: bool,
+    config: &Config,
+) -> Result<(), Error> {
+    let reveal = self::reveal(reveal);
+    let end = terminator(null);
+    let matches = matcher(&query);
+    let mut names = Tree::default();
+    let file = if archived {
+        with_suffix(&file, ".archive")
+    } else {
+        file
+    };
+    let mut ranked = Vec::new();
+    scan(&file, |entry| {
+        let data = match entry {
+            Entry::Valid(data) => data,
+            entry if archived => entry.into_data(),
+            _ => return Ok(()),
+        };
+        let rank = match rank(&data, &matches) {
+            Some(rank) => rank,
+            None => return Ok(()),
+        };
+        let line = if tree {
+            data.name.to_string()
+        } else if jsonl {
+            let password = Secret::new(fmt_entry("%P", &data, reveal));
+            json!({
+                "name": data.name,
+                "link": data.link,
+                "username": data.username,
+                "password": password.as_str(),
+                "tags": data.tags().collect::<Vec<_>>(),
+                "aliases": data.aliases().collect::<Vec<_>>(),
+            })
+            .to_string()
+        } else {
+            fmt_entry("%N (%L) %U %P", &data, reveal)
+        };
+        ranked.push((rank, data.name.to_string(), Secret::new(line)));
+        Ok(())
+    })?;
+    ranked.sort_by_key(|(rank, _, _)| *rank);
+
+    if reveal && !tree {
+        let names: Vec<&str> = ranked.iter().map(|(_, name, _)| name.as_str()).collect();
+        audit::record(config, "ls", &names)?;
+    }
+
+    for (_, _, line) in ranked {
+        if tree {
+            names.insert(&line);
+        } else {
+            print!("{}{}", line.as_str(), end);
+        }
+    }
+
+    if tree {
+        names.print();
+    }
+    Ok(())
+}
+
+fn terminator(null: bool) -> char {
+    if null {
+        '\0'
+    } else {
+        '\n'
+    }
+}
+
+fn names(file: PathBuf, query: Option<String>, null: bool) -> Result<(), Error> {
+    let end = terminator(null);
+    let matches = matcher(query.as_deref().unwrap_or_default());
+    let mut ranked = Vec::new();
+    scan(&file, |entry| {
+        if let Entry::Valid(data) = entry {
+            if let Some(rank) = rank(&data, &matches) {
+                ranked.push((rank, data.name.to_string()));
+            }
+        }
+        Ok(())
+    })?;
+    ranked.sort_by_key(|(rank, _)| *rank);
+
+    for (_, name) in ranked {
+        print!("{}{}", name, end);
+    }
+    Ok(())
+}
+
+pub fn main() -> Result<(), Error> {
+    let result = run();
+    if interrupted() {
+        process::exit(130);
+    }
+    result
+}
+
+fn run() -> Result<(), Error> {
+    let matches = Pw::clap().get_matches();
+    let subcommand = matches.subcommand_name().unwrap_or_default().to_string();
+    let opt = Pw::from_clap(&matches);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if let Some(selection) = opt.serve_clipboard {
+        clip::serve(selection);
+        return Ok(());
+    }
+    let config = Config::load()?;
+    let _ = MARKERS.set(config.markers().to_vec());
+    if let Some(prefix) = opt.complete_names {
+        complete::names(&prefix);
+        return Ok(());
+    }
+    let command = match opt.command {
+        Some(command) => command,
+        None => {
+            let _ = Pw::clap().print_help();
+            println!();
+            process::exit(1);
+        }
+    };
+
+    QUIET.store(opt.quiet, Ordering::Relaxed);
+    let log_level = match opt.verbose {
+        _ if opt.quiet => log::LevelFilter::Error,
+        0 => log::LevelFilter::Warn,
+        1 => log::LevelFilter::Info,
+        2 => log::LevelFilter::Debug,
+        _ => log::LevelFilter::Trace,
+    };
+    match (
+        opt.log_file.as_deref().or_else(|| config.log_file()),
+        opt.log_format,
+    ) {
+        (None, LogFormat::Text) => env_logger::Builder::new()
+            .filter_level(log_level)
+            .try_init()?,
+        (Some(log_file), format) => {
+            let out = file::open_append(log_file).map_err(Error::LogFile)?;
+            REDACT.store(true, Ordering::Relaxed);
+            logger::init(Box::new(out), log_level, format, subcommand)?;
+        }
+        (None, format) => logger::init(Box::new(io::stderr()), log_level, format, subcommand)?,
+    }
+    if let Some(file) = config.file() {
+        info!("Found config file at {}", file.display());
+    }
+
+    if cfg!(not(unix)) && matches!(command, Cmd::Exec { .. }) {
+        ctrlc::set_handler(|| {})?;
+    } else {
+        ctrlc::set_handler(|| {
+            clip::clear_interrupted();
+            if INTERRUPTED.swap(true, Ordering::Relaxed) {
+                process::exit(130);
+            }
+        })?;
+    }
+
+    if (opt.read_only || config.read_only()) && command.mutating() {
+        return Err(Error::ReadOnly);
+    }
+    file::set_dry_run(opt.dry_run);
+
+    let passfile = |file| {
+        let file = get_passfile(file)?;
+        sign::verify(&config, &file)?;
+        Ok::<_, Error>(file)
+    };
+
+    match command {
+        Cmd::Askpass { file, prompt } => askpass(passfile(file)?, prompt, &config),
+        Cmd::Attach(command) => attach(passfile(command.file().cloned())?, command, &config),
+        Cmd::Check {
+            file,
+            strict,
+            verbose,
+        } => check(passfile(file)?, strict, verbose),
+        Cmd::Completions { shell } => {
+            complete::script(shell);
+            Ok(())
+        }
+        Cmd::Compress { file, off } => compress(passfile(file)?, off),
+        Cmd::Derive {
+            site,
+            login,
+            counter,
+            policy,
+            clip,
+        } => {
+            let policy = match policy {
+                Some(name) => config.policy(Some(&name))?,
+                None => gen::Policy::lesspass(),
+            };
+            derive(
+                site,
+                login,
+                counter,
+                policy,
+                clip.map(Option::unwrap_or_default),
+                &config,
+            )
+        }
+        Cmd::DockerCredential { file, action } => {
+            docker_credential(passfile(file)?, action, &config)
+        }
+        Cmd::Exec {
+            file,
+            envs,
+            command,
+        } => {
+            let code = exec(&passfile(file)?, &envs, &command, &config)?;
+            if code != 0 {
+                process::exit(code);
+            }
+            Ok(())
+        }
+        Cmd::Export(command) => export(passfile(command.file().cloned())?, command, &config),
+        Cmd::Generate {
+            add: fields,
+            clip,
+            count,
+            policy,
+            no_ambiguous,
+            source,
+            verbose,
+            file,
+        } => {
+            let mut gen_policy = config.policy(policy.as_deref())?;
+            if no_ambiguous {
+                gen_policy = gen_policy.no_ambiguous();
+            }
+
+            let generator = config.generator(source);
+            match fields {
+                Some(fields) => add(
+                    passfile(file)?,
+                    fields,
+                    policy,
+                    gen_policy,
+                    clip.flatten().unwrap_or_default(),
+                    &generator,
+                    &config,
+                ),
+                None => gen::output(
+                    clip.map(Option::unwrap_or_default),
+                    count,
+                    &gen_policy,
+                    &generator,
+                    verbose,
+                ),
+            }
+        }
+        Cmd::GitCredential { file, action } => git_credential(passfile(file)?, action, &config),
+        Cmd::Get {
+            mut accs,
+            format,
+            fields,
+            url,
+            strict,
+            no_newline,
+            reveal,
+            file,
+        } => {
+            let (format, file) = if format.is_some() || !fields.is_empty() {
+                (format, file)
+            } else {
+                match (accs.len(), file) {
+                    (2, file) => (accs.pop(), file),
+                    (3, None) => {
+                        let file = accs.pop().map(PathBuf::from);
+                        (accs.pop(), file)
+                    }
+                    _ => return Err(Error::MissingFormat),
+                }
+            };
+            get(
+                passfile(file)?,
+                accs,
+                format,
+                fields,
+                url,
+                strict,
+                no_newline,
+                reveal,
+                &config,
+            )
+        }
+        Cmd::Import(command) => import(passfile(command.file().cloned())?, command, &config),
+        Cmd::List {
+            file,
+            query,
+            archived,
+            reveal,
+            tree,
+            jsonl,
+            null,
+        } => list(
+            passfile(file)?,
+            query,
+            archived,
+            reveal,
+            tree,
+            jsonl,
+            null,
+            &config,
+        ),
+        Cmd::Mark { file, acc, mark: m } => mark(passfile(file)?, acc, m),
+        Cmd::MatchUrl { file, url } => match_url(passfile(file)?, url),
+        Cmd::Move { file, old, new } => rename(passfile(file)?, old, new),
+        Cmd::Names { file, query, null } => names(passfile(file)?, query, null),
+        Cmd::Notify { file } => notify(passfile(file)?, &config),
+        Cmd::Open { file, acc, clip } => {
+            open(passfile(file)?, acc, clip.map(Option::unwrap_or_default))
+        }
+        Cmd::Otp(command) => otp(passfile(command.file().cloned())?, command),
+        Cmd::Recovery {
+            file,
+            acc,
+            use_code,
+        } => recovery(passfile(file)?, acc, use_code, &config),
+        Cmd::Remove { file, acc } => remove(passfile(file)?, acc),
+        Cmd::Render {
+            file,
+            template,
+            output,
+        } => render(&passfile(file)?, template, output, &config),
+        Cmd::Report { file } => report(passfile(file)?, &config),
+        Cmd::Rotate {
+            file,
+            acc,
+            clip,
+            source,
+        } => rotate(
+            passfile(file)?,
+            acc,
+            clip.map(Option::unwrap_or_default),
+            &config.generator(source),
+            &config,
+        ),
+        Cmd::Share {
+            file,
+            tags,
+            to,
+            output,
+        } => share(passfile(file)?, tags, to, output, &config),
+        Cmd::Show {
+            file,
+            acc,
+            reveal,
+            clip,
+        } => show(
+            passfile(file)?,
+            acc,
+            reveal,
+            clip.map(Option::unwrap_or_default),
+            &config,
+        ),
+        Cmd::Sign { file } => sign(get_passfile(file)?, &config),
+        Cmd::Split {
+            file,
+            dir,
+            by_prefix,
+        } => split(passfile(file)?, dir, by_prefix),
+        Cmd::Stats {
+            command: Some(Stats::Usernames { file, json }),
+            file: outer_file,
+            json: outer_json,
+        } => usernames(passfile(file.or(outer_file))?, json || outer_json),
+        Cmd::Stats { file, json, .. } => stats(passfile(file)?, json),
+        Cmd::SudoAskpass { file, prompt } => sudo_askpass(passfile(file)?, prompt, &config),
+        #[cfg(unix)]
+        Cmd::SystemdAgent { file, watch } => systemd_agent(passfile(file)?, watch, &config),
+        Cmd::Undo { file } => undo(passfile(file)?),
+        Cmd::Verify { file, update } => verify(passfile(file)?, update),
+        Cmd::WifiQr { file, acc, output } => wifi_qr(passfile(file)?, acc, output, &config),
+    }
+}
@@ -0,0 +1,29 @@
+use url::{Host, Url};
+
+pub fn parse(link: &str) -> Option<Url> {
+    Url::parse(link)
+        .ok()
+        .filter(|url| url.host_str().is_some())
+        .or_else(|| Url::parse(&format!("https://{}", link)).ok())
+        .filter(|url| url.host_str().is_some())
+}
+
+pub fn host(link: &str) -> Option<String> {
+    let url = parse(link)?;
+    let host = url.host_str()?;
+    Some(host.trim_start_matches("www.").to_string())
+}
+
+pub fn domain(link: &str) -> Option<String> {
+    let url = parse(link)?;
+    match url.host()? {
+        Host::Ipv4(_) | Host::Ipv6(_) => return Some(url.host_str()?.to_string()),
+        Host::Domain(_) => {}
+    }
+    let host = host(link)?;
+    if !host.contains('.') {
+        return Some(host);
+    }
+    let domain = psl::domain_str(&host).unwrap_or(&host);
+    Some(domain.to_string())
+}
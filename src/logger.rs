@@ -0,0 +1,72 @@
+use crate::cmdline::LogFormat;
+use humantime::format_rfc3339_seconds;
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+use serde_json::json;
+use std::io::Write;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+struct Logger {
+    out: Mutex<Box<dyn Write + Send>>,
+    level: LevelFilter,
+    format: LogFormat,
+    subcommand: String,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let timestamp = format_rfc3339_seconds(SystemTime::now());
+        if let Ok(mut out) = self.out.lock() {
+            let _ = match self.format {
+                LogFormat::Text => writeln!(
+                    out,
+                    "[{} {:5} {}] {}",
+                    timestamp,
+                    record.level(),
+                    record.target(),
+                    record.args()
+                ),
+                LogFormat::Json => writeln!(
+                    out,
+                    "{}",
+                    json!({
+                        "timestamp": timestamp.to_string(),
+                        "level": record.level().to_string(),
+                        "subcommand": self.subcommand,
+                        "target": record.target(),
+                        "message": record.args().to_string(),
+                    })
+                ),
+            };
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut out) = self.out.lock() {
+            let _ = out.flush();
+        }
+    }
+}
+
+pub fn init(
+    out: Box<dyn Write + Send>,
+    level: LevelFilter,
+    format: LogFormat,
+    subcommand: String,
+) -> Result<(), SetLoggerError> {
+    log::set_boxed_logger(Box::new(Logger {
+        out: Mutex::new(out),
+        level,
+        format,
+        subcommand,
+    }))?;
+    log::set_max_level(level);
+    Ok(())
+}
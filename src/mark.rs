@@ -0,0 +1,24 @@
+use crate::cmdline::Mark;
+use crate::err::Error;
+use crate::file;
+use crate::{find, read, ANY};
+use log::info;
+use std::path::PathBuf;
+
+pub fn mark(file: PathBuf, acc: String, mark: Mark) -> Result<(), Error> {
+    let data = read(&file)?;
+    let entry = find(&data, ANY, &acc)?;
+    let line = entry.line;
+
+    let edited = file::replace_line(&data, line, |line| {
+        file::replace_token(line, 0, mark.marker())
+    });
+
+    if edited == data {
+        info!("{} is already marked {} (skip)", acc, mark.marker());
+    } else {
+        file::write(&file, &edited)?;
+    }
+
+    Ok(())
+}
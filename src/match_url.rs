@@ -0,0 +1,38 @@
+use crate::err::Error;
+use crate::link;
+use crate::{parse, read, Entry};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+pub fn match_url(file: PathBuf, url: String) -> Result<(), Error> {
+    let host = link::host(&url).ok_or_else(|| Error::InvalidUrl(url.clone()))?;
+    let domain = link::domain(&url).ok_or_else(|| Error::InvalidUrl(url.clone()))?;
+
+    let data = read(file)?;
+    let entries = parse(&data);
+    let mut host_matches = Vec::new();
+    let mut domain_matches = Vec::new();
+    for entry in entries {
+        if let Entry::Valid(entry) = entry? {
+            let (matches, rank) = if link::host(entry.link).as_ref() == Some(&host) {
+                (&mut host_matches, "host")
+            } else if link::domain(entry.link).as_ref() == Some(&domain) {
+                (&mut domain_matches, "domain")
+            } else {
+                continue;
+            };
+
+            matches.push(json!({
+                "name": entry.name,
+                "link": entry.link,
+                "username": entry.username,
+                "match": rank,
+            }));
+        }
+    }
+
+    host_matches.append(&mut domain_matches);
+    println!("{}", Value::Array(host_matches));
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use crate::attach;
+use crate::err::Error;
+use crate::file;
+use crate::{check_value, find, parse, read, ANY};
+use std::path::PathBuf;
+
+pub fn rename(file: PathBuf, old: String, new: String) -> Result<(), Error> {
+    check_value("account name", &new)?;
+
+    let data = read(&file)?;
+    let entry = find(&data, ANY, &old)?;
+    let line = entry.line;
+    let name = entry.name;
+
+    for entry in parse(&data) {
+        if entry?.data().named(&new) {
+            return Err(Error::Exists(new));
+        }
+    }
+
+    let edited = file::replace_line(&data, line, |line| file::replace_token(line, 1, &new));
+    file::write(&file, &edited)?;
+    attach::rename(&file, name, &new)?;
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::config::Config;
+use crate::err::Error;
+use crate::{parse, read, Entry};
+use log::info;
+use notify_rust::Notification;
+use std::path::PathBuf;
+
+pub fn notify(file: PathBuf, config: &Config) -> Result<(), Error> {
+    let cutoff = config.changed_before();
+    let data = read(file)?;
+    let entries = parse(&data);
+    let mut change = 0;
+    for entry in entries {
+        match entry? {
+            Entry::Change(_) => change += 1,
+            Entry::Valid(entry) if entry.outdated(cutoff.as_deref()) => change += 1,
+            _ => {}
+        }
+    }
+
+    if change == 0 {
+        info!("No passwords need changing (skip notification)");
+        return Ok(());
+    }
+
+    let body = if change == 1 {
+        String::from("1 password needs changing")
+    } else {
+        format!("{} passwords need changing", change)
+    };
+
+    Notification::new()
+        .appname("pw")
+        .summary("Password changes pending")
+        .body(&body)
+        .show()
+        .map_err(Error::Notify)?;
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::clip;
+use crate::cmdline::Selection;
+use crate::err::Error;
+use crate::{find, read, VALID};
+use log::info;
+use std::path::PathBuf;
+use std::process;
+
+fn opener() -> (&'static str, &'static [&'static str]) {
+    if cfg!(target_os = "macos") {
+        ("open", &[])
+    } else if cfg!(windows) {
+        ("cmd", &["/C", "start", ""])
+    } else {
+        ("xdg-open", &[])
+    }
+}
+
+pub fn open(file: PathBuf, acc: String, clip: Option<Selection>) -> Result<(), Error> {
+    let data = read(file)?;
+    let entry = find(&data, VALID, &acc)?;
+
+    if let Some(selection) = clip {
+        clip::copy(entry.username, selection)?;
+    }
+
+    let (program, args) = opener();
+    info!("Opening {} with {}", entry.link, program);
+    let exit_status = process::Command::new(program)
+        .args(args)
+        .arg(entry.link)
+        .stdin(process::Stdio::null())
+        .status()
+        .map_err(|e| Error::OpenSpawn(program, e))?;
+
+    if !exit_status.success() {
+        return Err(Error::OpenErr(program));
+    }
+
+    Ok(())
+}
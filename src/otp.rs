@@ -0,0 +1,131 @@
+use crate::cmdline::Otp;
+use crate::err::Error;
+use crate::file;
+use crate::{find, parse, read, Entry, Secret, ACTIVE};
+use log::info;
+use std::path::{Path, PathBuf};
+
+const PARAMS: [(&str, &str); 3] = [("digits", "6"), ("period", "30"), ("algorithm", "SHA1")];
+
+pub fn normalize(secret: &str) -> Result<Secret<String>, Error> {
+    let secret = Secret::new(
+        secret
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .trim_end_matches('=')
+            .to_ascii_uppercase(),
+    );
+    if secret.is_empty()
+        || !secret
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || ('2'..='7').contains(&c))
+    {
+        return Err(Error::InvalidOtpSecret);
+    }
+    Ok(secret)
+}
+
+pub fn params(values: Vec<Option<String>>) -> Vec<(&'static str, String)> {
+    PARAMS
+        .iter()
+        .zip(values)
+        .filter_map(|(&(key, default), value)| Some((key, value.filter(|value| value != default)?)))
+        .collect()
+}
+
+pub fn param_tokens(line: &str) -> Vec<usize> {
+    line.split_whitespace()
+        .enumerate()
+        .skip(5)
+        .filter(|(_, token)| token.starts_with("otp-"))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+pub fn fields(n: u32, secret: &str, params: &[(&str, String)]) -> String {
+    let otp = match n {
+        1 => String::from("otp"),
+        n => format!("otp{}", n),
+    };
+    let params = params
+        .iter()
+        .map(|(key, value)| format!(" {}-{}={}", otp, key, value));
+    format!("{}={}{}", otp, secret, params.collect::<String>())
+}
+
+fn list(file: &Path) -> Result<(), Error> {
+    let data = read(file)?;
+    for entry in parse(&data) {
+        if let Entry::Valid(entry) = entry? {
+            if entry.field("otp").is_some() {
+                println!("{}", entry.name);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn add(file: &Path, acc: &str, secret: &str, params: Vec<(&str, String)>) -> Result<(), Error> {
+    let secret = normalize(secret)?;
+    let data = read(file)?;
+    let entry = find(&data, ACTIVE, acc)?;
+    if entry.field("otp").is_some() {
+        return Err(Error::OtpExists(entry.name.to_string()));
+    }
+
+    let edited = file::replace_line(&data, entry.line, |line| {
+        format!("{} {}", line.trim_end(), fields(1, &secret, &params))
+    });
+    file::write(file, &edited)?;
+    info!("Added an OTP secret to {}", entry.name);
+
+    Ok(())
+}
+
+fn remove(file: &Path, acc: &str) -> Result<(), Error> {
+    let data = read(file)?;
+    let entry = find(&data, ACTIVE, acc)?;
+    let index = entry
+        .fields()
+        .position(|(key, _)| key == "otp")
+        .ok_or_else(|| Error::NoOtp(entry.name.to_string()))?;
+
+    let edited = file::replace_line(&data, entry.line, |line| {
+        let mut tokens = param_tokens(line);
+        tokens.push(5 + index);
+        tokens.sort_unstable();
+        tokens
+            .into_iter()
+            .rev()
+            .fold(line.to_string(), |line, index| {
+                file::remove_token(&line, index)
+            })
+    });
+    file::write(file, &edited)?;
+    info!("Removed the OTP secret of {}", entry.name);
+
+    Ok(())
+}
+
+pub fn otp(file: PathBuf, command: Otp) -> Result<(), Error> {
+    match command {
+        Otp::Add {
+            acc,
+            secret,
+            digits,
+            period,
+            algorithm,
+            ..
+        } => {
+            let params = params(vec![
+                digits.map(|digits| digits.to_string()),
+                period.map(|period| period.to_string()),
+                algorithm.map(|algorithm| algorithm.to_uppercase()),
+            ]);
+            add(&file, &acc, &secret, params)
+        }
+        Otp::List { .. } => list(&file),
+        Otp::Remove { acc, .. } => remove(&file, &acc),
+    }
+}
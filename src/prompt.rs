@@ -0,0 +1,127 @@
+use crate::err::Error;
+use crate::Secret;
+use std::time::Duration;
+
+#[cfg(unix)]
+struct Echo {
+    fd: std::os::unix::io::RawFd,
+    saved: libc::termios,
+}
+
+#[cfg(unix)]
+impl Echo {
+    fn off(fd: std::os::unix::io::RawFd) -> Result<Self, Error> {
+        let mut saved = unsafe { std::mem::zeroed::<libc::termios>() };
+        if unsafe { libc::tcgetattr(fd, &mut saved) } != 0 {
+            return Err(Error::Prompt(std::io::Error::last_os_error()));
+        }
+        let mut hidden = saved;
+        hidden.c_lflag &= !libc::ECHO;
+        hidden.c_lflag |= libc::ECHONL;
+        if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &hidden) } != 0 {
+            return Err(Error::Prompt(std::io::Error::last_os_error()));
+        }
+        Ok(Echo { fd, saved })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Echo {
+    fn drop(&mut self) {
+        unsafe { libc::tcsetattr(self.fd, libc::TCSANOW, &self.saved) };
+    }
+}
+
+#[cfg(unix)]
+pub fn read(
+    prompt: &str,
+    hidden: bool,
+    timeout: Duration,
+) -> Result<Option<Secret<String>>, Error> {
+    use crate::interrupted;
+    use std::fs::OpenOptions;
+    use std::io::{self, Read, Write};
+    use std::os::unix::io::AsRawFd;
+    use std::time::Instant;
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .map_err(Error::Prompt)?;
+    tty.write_all(prompt.as_bytes()).map_err(Error::Prompt)?;
+    let _echo = if hidden {
+        Some(Echo::off(tty.as_raw_fd())?)
+    } else {
+        None
+    };
+
+    let deadline = Instant::now() + timeout;
+    let mut line = Secret::new(Vec::with_capacity(256));
+    let mut byte = [0u8; 1];
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if interrupted() || left == Duration::ZERO {
+            let _ = writeln!(tty);
+            if interrupted() {
+                return Err(Error::Interrupted);
+            }
+            return Err(Error::PromptTimeout(timeout.as_secs()));
+        }
+
+        let mut poll = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = left.as_millis().min(i32::MAX as u128) as i32;
+        match unsafe { libc::poll(&mut poll, 1, millis) } {
+            0 => continue,
+            ready if ready < 0 => match io::Error::last_os_error() {
+                e if e.kind() == io::ErrorKind::Interrupted => continue,
+                e => return Err(Error::Prompt(e)),
+            },
+            _ => {}
+        }
+
+        match tty.read(&mut byte) {
+            Ok(0) if line.is_empty() => return Ok(None),
+            Ok(0) => break,
+            Ok(_) if byte[0] == b'\n' => break,
+            Ok(_) => line.push(byte[0]),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(Error::Prompt(e)),
+        }
+    }
+
+    match String::from_utf8(std::mem::take(&mut *line)) {
+        Ok(line) => Ok(Some(Secret::new(line))),
+        Err(e) => {
+            drop(Secret::new(e.into_bytes()));
+            Err(Error::PromptUtf8)
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn read(
+    prompt: &str,
+    hidden: bool,
+    _timeout: Duration,
+) -> Result<Option<Secret<String>>, Error> {
+    use std::io;
+
+    if hidden {
+        let line = rpassword::prompt_password(prompt).map_err(Error::Prompt)?;
+        return Ok(Some(Secret::new(line)));
+    }
+
+    eprint!("{}", prompt);
+    let mut line = Secret::new(String::new());
+    if io::stdin().read_line(&mut line).map_err(Error::Prompt)? == 0 {
+        return Ok(None);
+    }
+    let len = line.trim_end_matches(&['\r', '\n'][..]).len();
+    line.truncate(len);
+    Ok(Some(line))
+}
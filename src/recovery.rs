@@ -0,0 +1,71 @@
+use crate::audit;
+use crate::config::Config;
+use crate::err::Error;
+use crate::file;
+use crate::{find, read, EntryData, Secret, ACTIVE};
+use log::info;
+use std::path::PathBuf;
+
+const USED: char = '-';
+
+pub fn codes<'a>(entry: &EntryData<'a>) -> impl Iterator<Item = &'a str> {
+    entry
+        .field("recovery")
+        .into_iter()
+        .flat_map(|codes| codes.split(','))
+        .filter(|code| !code.is_empty())
+}
+
+pub fn unused(code: &str) -> bool {
+    !code.starts_with(USED)
+}
+
+pub fn recovery(file: PathBuf, acc: String, use_code: bool, config: &Config) -> Result<(), Error> {
+    let data = read(&file)?;
+    let entry = find(&data, ACTIVE, &acc)?;
+    audit::record(config, "recovery", &[entry.name])?;
+
+    if !use_code {
+        let mut left = 0;
+        for code in codes(&entry).filter(|code| unused(code)) {
+            println!("{}", code);
+            left += 1;
+        }
+        if left == 0 {
+            return Err(Error::NoRecoveryCodes(acc));
+        }
+        return Ok(());
+    }
+
+    let codes: Vec<&str> = codes(&entry).collect();
+    let used = codes
+        .iter()
+        .position(|code| unused(code))
+        .ok_or_else(|| Error::NoRecoveryCodes(acc.clone()))?;
+    let mut field = Secret::new(String::from("recovery="));
+    for (i, code) in codes.iter().enumerate() {
+        if i > 0 {
+            field.push(',');
+        }
+        if i == used {
+            field.push(USED);
+        }
+        field.push_str(code);
+    }
+    let index = 5 + entry
+        .fields()
+        .position(|(key, _)| key == "recovery")
+        .unwrap_or_default();
+    let edited = file::replace_line(&data, entry.line, |line| {
+        file::replace_token(line, index, &field)
+    });
+
+    file::write(&file, &edited)?;
+    info!("Marked a recovery code of {} as used", acc);
+
+    if !file::dry_run() {
+        println!("{}", codes[used]);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,159 @@
+use crate::config::Config;
+use crate::err::Error;
+use crate::exec;
+use crate::file;
+use crate::Secret;
+use log::info;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+const COMMAND: &str = "pw";
+
+struct Placeholder<'a> {
+    range: Range<usize>,
+    acc: &'a str,
+    format: &'a str,
+}
+
+fn arguments(mut args: &str) -> Option<Vec<&str>> {
+    let mut out = Vec::new();
+    loop {
+        args = args.trim_start();
+        if args.is_empty() {
+            return Some(out);
+        }
+        args = args.strip_prefix('"')?;
+        let end = args.find('"')?;
+        out.push(&args[..end]);
+        args = &args[end + 1..];
+    }
+}
+
+fn ours(placeholder: &str) -> bool {
+    placeholder
+        .trim_start()
+        .strip_prefix(COMMAND)
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(|c| c.is_whitespace() || c == '}')
+}
+
+fn placeholders(template: &str) -> Result<Vec<Placeholder<'_>>, Error> {
+    let mut out = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = template[offset..].find(OPEN).map(|i| offset + i) {
+        if !ours(&template[start + OPEN.len()..]) {
+            offset = start + 1;
+            continue;
+        }
+        let line = template[..start].matches('\n').count() + 1;
+        let end = template[start..]
+            .find(CLOSE)
+            .map(|i| start + i + CLOSE.len())
+            .ok_or_else(|| Error::Template(line, String::from("missing }}")))?;
+        let inner = template[start + OPEN.len()..end - CLOSE.len()].trim();
+        let args = inner
+            .strip_prefix(COMMAND)
+            .and_then(arguments)
+            .ok_or_else(|| {
+                Error::Template(
+                    line,
+                    format!("expected pw \"account\" \"format\", got {}", inner),
+                )
+            })?;
+        let (acc, format) = match args[..] {
+            [acc] => (acc, "%P"),
+            [acc, format] => (acc, format),
+            _ => {
+                return Err(Error::Template(
+                    line,
+                    String::from("expected 1 or 2 arguments"),
+                ))
+            }
+        };
+        out.push(Placeholder {
+            range: start..end,
+            acc,
+            format,
+        });
+        offset = end;
+    }
+    Ok(out)
+}
+
+pub fn render(
+    file: &Path,
+    template: PathBuf,
+    output: Option<PathBuf>,
+    config: &Config,
+) -> Result<(), Error> {
+    let template = fs::read_to_string(&template).map_err(Error::TemplateRead)?;
+    let placeholders = placeholders(&template)?;
+    let queries: Vec<(&str, &str)> = placeholders
+        .iter()
+        .map(|placeholder| (placeholder.acc, placeholder.format))
+        .collect();
+    let values = exec::fields(file, &queries, "render", config)?;
+
+    let mut rendered = Secret::new(String::with_capacity(template.len()));
+    let mut offset = 0;
+    for (placeholder, value) in placeholders.iter().zip(&values) {
+        rendered.push_str(&template[offset..placeholder.range.start]);
+        rendered.push_str(value);
+        offset = placeholder.range.end;
+    }
+    rendered.push_str(&template[offset..]);
+
+    match output {
+        Some(output) => {
+            file::write_private(&output, rendered.as_bytes()).map_err(Error::RenderWrite)?;
+            info!(
+                "Rendered {} placeholders into {}",
+                values.len(),
+                output.display()
+            );
+        }
+        None => print!("{}", *rendered),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::placeholders;
+    use crate::err::Error;
+
+    #[test]
+    fn nested() {
+        let template = "{{ {{ pw \"github\" }} }}";
+        let placeholders = placeholders(template).unwrap();
+        assert_eq!(placeholders.len(), 1);
+        assert_eq!(
+            &template[placeholders[0].range.clone()],
+            "{{ pw \"github\" }}"
+        );
+        assert_eq!(placeholders[0].acc, "github");
+        assert_eq!(placeholders[0].format, "%P");
+    }
+
+    #[test]
+    fn unterminated() {
+        let template = "user: admin\npassword: {{ pw \"github\"\n";
+        assert!(matches!(placeholders(template), Err(Error::Template(2, _))));
+    }
+
+    #[test]
+    fn foreign() {
+        let template = "{{ .Values.user }}: {{pw \"github\" \"%U\"}} {{pwd}}";
+        let placeholders = placeholders(template).unwrap();
+        assert_eq!(placeholders.len(), 1);
+        assert_eq!(
+            &template[placeholders[0].range.clone()],
+            "{{pw \"github\" \"%U\"}}"
+        );
+        assert_eq!(placeholders[0].format, "%U");
+    }
+}
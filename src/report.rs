@@ -0,0 +1,153 @@
+use crate::config::Config;
+use crate::err::Error;
+use crate::link;
+use crate::{parse, read, Entry, EntryData};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::PathBuf;
+
+fn weaknesses(entry: &EntryData, min_length: usize) -> Vec<String> {
+    let password = entry.password;
+    let lowercase = password.to_lowercase();
+    let mut weak = Vec::new();
+    if password.chars().all(|c| c.is_ascii_lowercase()) {
+        weak.push(String::from("all lowercase"));
+    }
+    if password.chars().all(|c| c.is_ascii_digit()) {
+        weak.push(String::from("all digits"));
+    }
+    if password.chars().count() < min_length {
+        weak.push(format!("shorter than {}", min_length));
+    }
+    if password.eq_ignore_ascii_case(entry.username) {
+        weak.push(String::from("same as the username"));
+    }
+    if entry
+        .name
+        .split('/')
+        .filter(|part| part.len() >= 3)
+        .any(|part| lowercase.contains(&part.to_lowercase()))
+    {
+        weak.push(String::from("contains the account name"));
+    }
+    weak
+}
+
+type Reuse<'a> = BTreeMap<String, Vec<(usize, &'a str, Vec<String>)>>;
+
+fn reuse<'a>(passwords: HashMap<&str, Vec<(usize, String, &'a str)>>) -> (Reuse<'a>, Reuse<'a>) {
+    let mut across = Reuse::new();
+    let mut within = Reuse::new();
+    for users in passwords.values().filter(|users| users.len() > 1) {
+        let domains: BTreeSet<&String> = users.iter().map(|(_, domain, _)| domain).collect();
+        for (line, domain, name) in users {
+            let others: Vec<String> = domains
+                .iter()
+                .filter(|other| *other != &domain)
+                .map(|other| other.to_string())
+                .collect();
+            let report = if others.is_empty() {
+                &mut within
+            } else {
+                &mut across
+            };
+            report
+                .entry(domain.clone())
+                .or_default()
+                .push((*line, name, others));
+        }
+    }
+    for users in across.values_mut().chain(within.values_mut()) {
+        users.sort();
+    }
+    (across, within)
+}
+
+fn print_reuse(title: &str, report: &Reuse) {
+    if report.is_empty() {
+        return;
+    }
+    println!();
+    println!("{}:", title);
+    for (domain, users) in report {
+        println!("  {}", domain);
+        for (_, name, others) in users {
+            if others.is_empty() {
+                println!("    {}", name);
+            } else {
+                println!("    {} (also on {})", name, others.join(", "));
+            }
+        }
+    }
+}
+
+pub fn report(file: PathBuf, config: &Config) -> Result<(), Error> {
+    let cutoff = config.changed_before();
+    let data = read(file)?;
+    let entries = parse(&data);
+    let mut valid = 0;
+    let mut invalid = 0;
+    let mut change = Vec::new();
+    let mut weak = Vec::new();
+    let mut passwords: HashMap<&str, Vec<_>> = HashMap::new();
+    for entry in entries {
+        match entry? {
+            Entry::Valid(entry) => {
+                valid += 1;
+                if entry.outdated(cutoff.as_deref()) {
+                    change.push((entry.line, entry.name));
+                }
+                let weaknesses = weaknesses(&entry, config.min_length());
+                if !weaknesses.is_empty() {
+                    weak.push((entry.line, entry.name, weaknesses.join(", ")));
+                }
+                let domain = link::domain(entry.link).unwrap_or_else(|| entry.link.to_lowercase());
+                passwords
+                    .entry(entry.password)
+                    .or_default()
+                    .push((entry.line, domain, entry.name));
+            }
+            Entry::Invalid(_) => invalid += 1,
+            Entry::Change(entry) => change.push((entry.line, entry.name)),
+        }
+    }
+    let (across, within) = reuse(passwords);
+
+    println!(
+        "{} current, {} inactive, {} need changing, {} weak, {} reused across domains",
+        valid,
+        invalid,
+        change.len(),
+        weak.len(),
+        across.values().map(Vec::len).sum::<usize>()
+    );
+
+    if !change.is_empty() {
+        println!();
+        println!("Need changing:");
+        for (_, name) in &change {
+            println!("  {}", name);
+        }
+    }
+
+    if !weak.is_empty() {
+        println!();
+        println!("Weak:");
+        for (_, name, weaknesses) in &weak {
+            println!("  {}: {}", name, weaknesses);
+        }
+    }
+
+    print_reuse("Reused across domains", &across);
+    print_reuse("Shared within a domain", &within);
+
+    let attention: BTreeSet<usize> = change
+        .iter()
+        .map(|(line, _)| *line)
+        .chain(weak.iter().map(|(line, _, _)| *line))
+        .chain(across.values().flatten().map(|(line, _, _)| *line))
+        .collect();
+    match attention.len() {
+        0 => Ok(()),
+        n => Err(Error::ActionNeeded(n)),
+    }
+}
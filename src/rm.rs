@@ -0,0 +1,26 @@
+use crate::err::Error;
+use crate::file;
+use crate::{find, read, ANY};
+use log::info;
+use std::path::PathBuf;
+
+pub fn remove(file: PathBuf, acc: String) -> Result<(), Error> {
+    let data = read(&file)?;
+    let entry = find(&data, ANY, &acc)?;
+    let line = entry.line;
+    let (edited, removed) = file::remove_line(&data, line);
+
+    let archive = file::with_suffix(&file, ".archive");
+    if file::dry_run() {
+        println!("Would archive {}:", archive.display());
+        println!("  + {}", file::describe(&removed));
+    } else {
+        file::append(&archive, &removed).map_err(Error::ArchiveWrite)?;
+    }
+
+    file::write(&file, &edited)?;
+
+    info!("Moved {} to {}", acc, archive.display());
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::audit;
+use crate::clip;
+use crate::cmdline::Selection;
+use crate::config::Config;
+use crate::err::Error;
+use crate::file;
+use crate::gen::Generator;
+use crate::{find, read, ACTIVE};
+use log::info;
+use std::path::PathBuf;
+
+pub fn rotate(
+    file: PathBuf,
+    acc: String,
+    clip: Option<Selection>,
+    generator: &Generator,
+    config: &Config,
+) -> Result<(), Error> {
+    let data = read(&file)?;
+    let entry = find(&data, ACTIVE, &acc)?;
+    audit::record(config, "rotate", &[entry.name])?;
+    let line = entry.line;
+    let policy = config.policy(entry.field("policy"))?;
+
+    let password = generator.generate(&policy)?;
+    let edited = file::replace_line(&data, line, |line| {
+        let line = file::replace_token(line, 4, &password);
+        let line = file::set_field(&line, "changed", &file::today());
+        file::replace_token(&line, 0, "+")
+    });
+
+    file::write(&file, &edited)?;
+    info!("Rotated the password of {}", acc);
+
+    if !file::dry_run() {
+        if let Some(selection) = clip {
+            clip::copy(&password, selection)?;
+        } else {
+            println!("{}", *password);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use crate::audit;
+use crate::config::Config;
+use crate::err::Error;
+use crate::{parse, read, Entry, Secret};
+use log::info;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+fn encrypter(to: &str) -> (&'static str, Vec<&str>) {
+    if to.starts_with("age1") {
+        ("age", vec!["--armor", "--recipient", to])
+    } else {
+        (
+            "gpg",
+            vec![
+                "--batch",
+                "--yes",
+                "--armor",
+                "--encrypt",
+                "--recipient",
+                to,
+            ],
+        )
+    }
+}
+
+pub fn share(
+    file: PathBuf,
+    tags: Vec<String>,
+    to: String,
+    output: Option<PathBuf>,
+    config: &Config,
+) -> Result<(), Error> {
+    let data = read(file)?;
+    let lines: Vec<&str> = data.lines().collect();
+    let mut shared = Secret::new(String::new());
+    let mut names = Vec::new();
+    for entry in parse(&data) {
+        if let Entry::Valid(entry) = entry? {
+            if entry.tags().any(|tag| tags.iter().any(|t| t == tag)) {
+                shared.push_str(lines[entry.line - 1].trim());
+                shared.push('\n');
+                names.push(entry.name);
+            }
+        }
+    }
+
+    if names.is_empty() {
+        return Err(Error::NoMatches(format!("tags {}", tags.join(", "))));
+    }
+    audit::record(config, "share", &names)?;
+
+    let (program, args) = encrypter(&to);
+    let mut command = process::Command::new(program);
+    command.args(args);
+    if let Some(output) = &output {
+        command.arg("--output").arg(output);
+    }
+    let mut child = command
+        .stdin(process::Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::ShareSpawn(program, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(shared.as_bytes())
+            .map_err(Error::ShareWrite)?;
+    }
+
+    let exit_status = child.wait().map_err(Error::ShareWait)?;
+    if !exit_status.success() {
+        return Err(Error::ShareErr(program));
+    }
+    info!("Shared {} account(s) with {}", names.len(), to);
+
+    Ok(())
+}
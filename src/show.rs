@@ -0,0 +1,82 @@
+use crate::audit;
+use crate::clip;
+use crate::cmdline::{Mark, Selection};
+use crate::config::Config;
+use crate::err::Error;
+use crate::recovery;
+use crate::{custom_marker, find, parse_mark, read, EntryData, ANY, MASK};
+use std::path::PathBuf;
+
+fn question(key: &str, prefix: &str) -> Option<u32> {
+    key.strip_prefix(prefix)?.parse().ok()
+}
+
+fn state(entry: &EntryData) -> &'static str {
+    if let Some(custom) = custom_marker(entry.marker) {
+        return &custom.name;
+    }
+    match parse_mark(entry.marker) {
+        Some(Mark::Valid) => "current",
+        Some(Mark::Invalid) => "inactive",
+        _ => "needs changing",
+    }
+}
+
+pub fn show(
+    file: PathBuf,
+    acc: String,
+    reveal: bool,
+    clip: Option<Selection>,
+    config: &Config,
+) -> Result<(), Error> {
+    let data = read(file)?;
+    let entry = find(&data, ANY, &acc)?;
+    let state = state(&entry);
+
+    if reveal || clip.is_some() {
+        audit::record(config, "show", &[entry.name])?;
+    }
+
+    if let Some(selection) = clip {
+        clip::copy(entry.password, selection)?;
+    }
+
+    let password = if reveal { entry.password } else { MASK };
+    println!("Name:     {}", entry.name);
+    println!("Link:     {}", entry.link);
+    println!("Username: {}", entry.username);
+    println!("Password: {}", password);
+    println!("State:    {}", state);
+
+    let tags: Vec<&str> = entry.tags().collect();
+    if !tags.is_empty() {
+        println!("Tags:     {}", tags.join(", "));
+    }
+    for (key, value) in entry.fields() {
+        match key {
+            "tags" => {}
+            "changed" => println!("Changed:  {}", value),
+            "policy" => println!("Policy:   {}", value),
+            "otp" => println!("OTP:      {}", if reveal { value } else { MASK }),
+            "recovery" => println!(
+                "Recovery: {} of {} codes left",
+                recovery::codes(&entry)
+                    .filter(|code| recovery::unused(code))
+                    .count(),
+                recovery::codes(&entry).count()
+            ),
+            key => match (question(key, "q"), question(key, "a"), question(key, "otp")) {
+                (Some(n), _, _) => {
+                    println!("{:9} {}", format!("Q{}:", n), value.replace('_', " "))
+                }
+                (_, Some(n), _) if !reveal => println!("{:9} {}", format!("A{}:", n), MASK),
+                (_, Some(n), _) => println!("{:9} {}", format!("A{}:", n), value),
+                (_, _, Some(n)) if !reveal => println!("{:9} {}", format!("OTP{}:", n), MASK),
+                (_, _, Some(n)) => println!("{:9} {}", format!("OTP{}:", n), value),
+                _ => println!("{:9} {}", format!("{}:", key), value),
+            },
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use crate::config::Config;
+use crate::err::Error;
+use crate::file;
+use log::info;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const GPG: &str = "gpg";
+
+fn signed_by(status: &str, key: &str) -> bool {
+    status
+        .lines()
+        .filter_map(|line| line.strip_prefix("[GNUPG:] VALIDSIG "))
+        .any(|line| {
+            let fingerprints = line.split_whitespace();
+            let primary = fingerprints.clone().last();
+            fingerprints
+                .take(1)
+                .chain(primary)
+                .any(|fingerprint| fingerprint == key)
+        })
+}
+
+pub fn verify(config: &Config, file: &Path) -> Result<(), Error> {
+    let key = match config.gpg_key() {
+        Some(key) => key,
+        None => return Ok(()),
+    };
+
+    let sig = file::with_suffix(file, ".sig");
+    if !sig.is_file() {
+        return Err(Error::NoSignature(sig.display().to_string()));
+    }
+
+    let output = process::Command::new(GPG)
+        .arg("--batch")
+        .args(["--status-fd", "1"])
+        .arg("--verify")
+        .arg(&sig)
+        .arg(file)
+        .stdin(process::Stdio::null())
+        .stderr(process::Stdio::null())
+        .output()
+        .map_err(Error::GpgSpawn)?;
+
+    let status = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() || !signed_by(&status, key) {
+        return Err(Error::BadSignature(sig.display().to_string()));
+    }
+    info!("Verified {} against key {}", sig.display(), key);
+
+    Ok(())
+}
+
+pub fn sign(file: PathBuf, config: &Config) -> Result<(), Error> {
+    let key = config.gpg_key().ok_or(Error::NoGpgKey)?;
+    let sig = file::with_suffix(&file, ".sig");
+    if file::dry_run() {
+        println!("Would write {}", sig.display());
+        return Ok(());
+    }
+
+    let exit_status = process::Command::new(GPG)
+        .arg("--batch")
+        .arg("--yes")
+        .args(["--local-user", key])
+        .arg("--detach-sign")
+        .arg("--output")
+        .arg(&sig)
+        .arg(&file)
+        .stdin(process::Stdio::null())
+        .status()
+        .map_err(Error::GpgSpawn)?;
+
+    if !exit_status.success() {
+        return Err(Error::GpgErr);
+    }
+    info!("Wrote signature {}", sig.display());
+
+    Ok(())
+}
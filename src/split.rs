@@ -0,0 +1,133 @@
+use crate::err::Error;
+use crate::file;
+use crate::{read, Secret};
+use log::info;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const INDEX: &str = "index";
+const DEFAULT: &str = "default";
+
+fn namespace(line: &str) -> Option<&str> {
+    let mut tokens = line.split_whitespace();
+    match (tokens.next(), tokens.next()) {
+        (Some(marker), Some(name)) if !marker.starts_with('#') => match name.split_once('/') {
+            Some((namespace, _)) if !namespace.is_empty() => Some(namespace),
+            _ => Some(DEFAULT),
+        },
+        _ => None,
+    }
+}
+
+fn index(dir: &Path) -> Result<Vec<String>, Error> {
+    let index = match fs::read_to_string(dir.join(INDEX)) {
+        Ok(index) => index,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(Error::PassFile(e)),
+    };
+    Ok(index
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+pub fn files(dir: &Path) -> Result<Vec<PathBuf>, Error> {
+    Ok(Some(dir.join(INDEX))
+        .into_iter()
+        .chain(index(dir)?.iter().map(|name| dir.join(name)))
+        .collect())
+}
+
+pub fn load(dir: &Path) -> Result<Secret<Vec<u8>>, Error> {
+    let mut data = Secret::new(Vec::new());
+    for name in index(dir)? {
+        let bytes = file::decode(fs::read(dir.join(&name)).map_err(Error::PassFile)?)?;
+        data.extend_from_slice(&bytes);
+        if !bytes.is_empty() && !bytes.ends_with(b"\n") {
+            data.push(b'\n');
+        }
+    }
+    Ok(data)
+}
+
+pub fn store(dir: &Path, data: &str) -> Result<(), Error> {
+    let mut files: Vec<(String, Secret<String>)> = index(dir)?
+        .into_iter()
+        .map(|name| (name, Secret::default()))
+        .collect();
+    let old_len = files.len();
+
+    let mut pending = Secret::new(String::new());
+    for line in data.split_inclusive('\n') {
+        let namespace = match namespace(line) {
+            Some(namespace) => namespace,
+            None => {
+                pending.push_str(line);
+                continue;
+            }
+        };
+        let name = format!("{}.pw", namespace);
+        let i = match files.iter().position(|(file, _)| *file == name) {
+            Some(i) => i,
+            None => {
+                files.push((name, Secret::default()));
+                files.len() - 1
+            }
+        };
+        let content = &mut files[i].1;
+        content.push_str(&pending);
+        pending.clear();
+        content.push_str(line);
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+    }
+    if !pending.is_empty() {
+        if files.is_empty() {
+            files.push((format!("{}.pw", DEFAULT), Secret::default()));
+        }
+        if let Some((_, content)) = files.last_mut() {
+            content.push_str(&pending);
+        }
+    }
+
+    for (name, content) in &files {
+        let path = dir.join(name);
+        let current = if path.is_file() {
+            read(&path)?
+        } else {
+            Secret::default()
+        };
+        if *content != current || !path.is_file() {
+            file::store(&path, content, file::compressed(&path))?;
+        }
+    }
+
+    if files.len() != old_len {
+        let index: String = files
+            .iter()
+            .map(|(name, _)| format!("{}\n", name))
+            .collect();
+        file::store(&dir.join(INDEX), &index, false)?;
+    }
+
+    Ok(())
+}
+
+pub fn split(file: PathBuf, dir: PathBuf, by_prefix: bool) -> Result<(), Error> {
+    if !by_prefix {
+        return Err(Error::SplitMode);
+    }
+
+    let data = read(&file)?;
+    if !file::dry_run() {
+        fs::create_dir(&dir).map_err(Error::SplitDir)?;
+    }
+    store(&dir, &data)?;
+    info!("Split {} into {}", file.display(), dir.display());
+
+    Ok(())
+}
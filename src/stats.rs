@@ -0,0 +1,149 @@
+use crate::err::Error;
+use crate::link;
+use crate::{parse, read};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+#[derive(Default)]
+struct Classes {
+    lowercase: usize,
+    uppercase: usize,
+    digits: usize,
+    symbols: usize,
+    other: usize,
+}
+
+impl Classes {
+    fn count(&mut self, password: &str) {
+        let has = |f: fn(&char) -> bool| password.chars().any(|c| f(&c));
+        self.lowercase += has(char::is_ascii_lowercase) as usize;
+        self.uppercase += has(char::is_ascii_uppercase) as usize;
+        self.digits += has(char::is_ascii_digit) as usize;
+        self.symbols += has(char::is_ascii_punctuation) as usize;
+        self.other += has(|c| !c.is_ascii_alphanumeric() && !c.is_ascii_punctuation()) as usize;
+    }
+}
+
+pub fn stats(file: PathBuf, json: bool) -> Result<(), Error> {
+    let data = read(file)?;
+    let entries = parse(&data);
+    let mut total = 0;
+    let mut domains = BTreeMap::new();
+    let mut lengths = BTreeMap::new();
+    let mut classes = Classes::default();
+    let mut tags = BTreeMap::new();
+    let mut oldest: Option<(String, String)> = None;
+    let mut newest: Option<(String, String)> = None;
+    for entry in entries {
+        let entry = entry?;
+        let entry = entry.data();
+
+        total += 1;
+        let domain = link::domain(entry.link).unwrap_or_else(|| entry.link.to_lowercase());
+        *domains.entry(domain).or_insert(0) += 1;
+        *lengths.entry(entry.password.chars().count()).or_insert(0) += 1;
+        classes.count(entry.password);
+        for tag in entry.tags() {
+            *tags.entry(tag.to_string()).or_insert(0) += 1;
+        }
+
+        if let Some(changed) = entry.field("changed") {
+            if oldest
+                .as_ref()
+                .is_none_or(|(date, _)| changed < date.as_str())
+            {
+                oldest = Some((changed.to_string(), entry.name.to_string()));
+            }
+            if newest
+                .as_ref()
+                .is_none_or(|(date, _)| changed > date.as_str())
+            {
+                newest = Some((changed.to_string(), entry.name.to_string()));
+            }
+        }
+    }
+
+    if json {
+        let dated = |entry: Option<(String, String)>| {
+            entry.map(|(changed, name)| json!({ "name": name, "changed": changed }))
+        };
+        let stats = json!({
+            "entries": total,
+            "domains": domains,
+            "lengths": lengths,
+            "classes": json!({
+                "lowercase": classes.lowercase,
+                "uppercase": classes.uppercase,
+                "digits": classes.digits,
+                "symbols": classes.symbols,
+                "other": classes.other,
+            }),
+            "tags": tags,
+            "oldest": dated(oldest),
+            "newest": dated(newest),
+        });
+        println!("{:#}", stats);
+        return Ok(());
+    }
+
+    println!("{} entries", total);
+
+    println!();
+    println!("Domains:");
+    for (domain, count) in &domains {
+        println!("  {}: {}", domain, count);
+    }
+
+    println!();
+    println!("Password lengths:");
+    for (length, count) in &lengths {
+        println!("  {}: {}", length, count);
+    }
+
+    println!();
+    println!("Character classes:");
+    println!("  lowercase: {}", classes.lowercase);
+    println!("  uppercase: {}", classes.uppercase);
+    println!("  digits: {}", classes.digits);
+    println!("  symbols: {}", classes.symbols);
+    println!("  other: {}", classes.other);
+
+    if !tags.is_empty() {
+        println!();
+        println!("Tags:");
+        for (tag, count) in &tags {
+            println!("  {}: {}", tag, count);
+        }
+    }
+
+    if let (Some((oldest, oldest_name)), Some((newest, newest_name))) = (oldest, newest) {
+        println!();
+        println!("Oldest: {} ({})", oldest_name, oldest);
+        println!("Newest: {} ({})", newest_name, newest);
+    }
+
+    Ok(())
+}
+
+pub fn usernames(file: PathBuf, json: bool) -> Result<(), Error> {
+    let data = read(file)?;
+    let mut usernames = BTreeMap::new();
+    for entry in parse(&data) {
+        let entry = entry?;
+        *usernames.entry(entry.data().username).or_insert(0) += 1;
+    }
+
+    if json {
+        println!("{:#}", json!(usernames));
+        return Ok(());
+    }
+
+    let mut counts: Vec<(&str, usize)> = usernames.into_iter().collect();
+    counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    for (username, count) in counts {
+        println!("{:>5}  {}", count, username);
+    }
+
+    Ok(())
+}
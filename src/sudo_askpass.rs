@@ -0,0 +1,79 @@
+use crate::audit;
+use crate::config::Config;
+use crate::err::Error;
+use crate::{find, read, VALID};
+use log::info;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+const PINENTRY: &str = "pinentry";
+
+fn escape(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn reply(line: &str) -> Option<&str> {
+    line.split(' ')
+        .next()
+        .filter(|status| *status == "OK" || *status == "ERR")
+}
+
+fn confirm(description: &str, timeout: Duration) -> Result<bool, Error> {
+    let mut child = process::Command::new(PINENTRY)
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::null())
+        .spawn()
+        .map_err(Error::PinentrySpawn)?;
+
+    let commands = [
+        String::from("SETTITLE pw"),
+        format!("SETDESC {}", escape(description)),
+        String::from("SETOK Allow"),
+        String::from("SETCANCEL Deny"),
+        format!("SETTIMEOUT {}", timeout.as_secs()),
+        String::from("CONFIRM"),
+        String::from("BYE"),
+    ];
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(format!("{}\n", commands.join("\n")).as_bytes())
+            .map_err(Error::PinentrySpawn)?;
+    }
+
+    let mut out = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        stdout
+            .read_to_string(&mut out)
+            .map_err(Error::PinentrySpawn)?;
+    }
+    let status = child.wait().map_err(Error::PinentrySpawn)?;
+
+    let confirm = commands.iter().position(|command| command == "CONFIRM");
+    let answer = confirm.and_then(|confirm| out.lines().filter_map(reply).nth(confirm + 1));
+    Ok(status.success() && answer == Some("OK"))
+}
+
+pub fn sudo_askpass(file: PathBuf, prompt: Option<String>, config: &Config) -> Result<(), Error> {
+    let acc = config.sudo_account().ok_or(Error::NoSudoAccount)?;
+    let data = read(file)?;
+    let entry = find(&data, VALID, acc)?;
+
+    let mut description = format!("Allow sudo to use the password of {}?", acc);
+    if let Some(prompt) = prompt {
+        description = format!("{}\n\n{}", prompt.trim(), description);
+    }
+    if !confirm(&description, config.prompt_timeout())? {
+        return Err(Error::Declined(acc.to_string()));
+    }
+    audit::record(config, "sudo-askpass", &[acc])?;
+
+    println!("{}", entry.password);
+    info!("Gave sudo the password of {}", acc);
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use crate::audit;
+use crate::config::Config;
+use crate::err::Error;
+use crate::{find_unique, interrupted, read, Secret, VALID};
+use log::{info, warn};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+const ASK_DIR: &str = "/run/systemd/ask-password";
+const POLL: Duration = Duration::from_secs(1);
+
+struct Ask {
+    id: String,
+    socket: PathBuf,
+}
+
+fn monotonic_usec() -> u64 {
+    let mut now = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut now) };
+    now.tv_sec as u64 * 1_000_000 + now.tv_nsec as u64 / 1_000
+}
+
+fn alive(pid: libc::pid_t) -> bool {
+    let found = unsafe { libc::kill(pid, 0) } == 0;
+    found || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+impl Ask {
+    fn load(path: &Path) -> Option<Self> {
+        let data = fs::read_to_string(path).ok()?;
+        let mut id = None;
+        let mut socket = None;
+        let mut not_after = None;
+        let mut pid = None;
+        for line in data.lines() {
+            match line.split_once('=') {
+                Some(("Id", value)) => id = Some(value.to_string()),
+                Some(("Socket", value)) => socket = Some(PathBuf::from(value)),
+                Some(("NotAfter", value)) => not_after = value.parse::<u64>().ok(),
+                Some(("PID", value)) => pid = value.parse::<libc::pid_t>().ok(),
+                _ => {}
+            }
+        }
+        if not_after.is_some_and(|not_after| not_after > 0 && not_after < monotonic_usec()) {
+            info!("{} has expired (skip)", path.display());
+            return None;
+        }
+        if pid.is_some_and(|pid| pid > 0 && !alive(pid)) {
+            info!(
+                "{} was asked by a process that has exited (skip)",
+                path.display()
+            );
+            return None;
+        }
+        Some(Ask {
+            id: id?,
+            socket: socket?,
+        })
+    }
+}
+
+fn answer(file: &Path, ask: &Ask, config: &Config) -> Result<bool, Error> {
+    let data = read(file)?;
+    let entry = match find_unique(&data, VALID, &ask.id, |entry| entry.link == ask.id)? {
+        Some(entry) => entry,
+        None => return Ok(false),
+    };
+
+    audit::record(config, "systemd-agent", &[entry.name])?;
+    let reply = Secret::new(format!("+{}", entry.password));
+    UnixDatagram::unbound()
+        .and_then(|socket| socket.send_to(reply.as_bytes(), &ask.socket))
+        .map_err(Error::AgentReply)?;
+    info!("Answered {} with the password of {}", ask.id, entry.name);
+
+    Ok(true)
+}
+
+fn scan(file: &Path, answered: &mut HashSet<PathBuf>, config: &Config) -> Result<(), Error> {
+    let entries = match fs::read_dir(ASK_DIR) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(Error::AgentRead(e)),
+    };
+
+    let mut current = HashSet::new();
+    for entry in entries {
+        let path = entry.map_err(Error::AgentRead)?.path();
+        let is_ask = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with("ask."));
+        if !is_ask {
+            continue;
+        }
+        current.insert(path.clone());
+        if answered.contains(&path) {
+            continue;
+        }
+
+        let ask = match Ask::load(&path) {
+            Some(ask) => ask,
+            None => continue,
+        };
+        match answer(file, &ask, config) {
+            Ok(true) => {
+                answered.insert(path);
+            }
+            Ok(false) => info!("No account links to {} (skip)", ask.id),
+            Err(e) => warn!("Could not answer {}: {}", ask.id, e),
+        }
+    }
+    answered.retain(|path| current.contains(path));
+
+    Ok(())
+}
+
+pub fn systemd_agent(file: PathBuf, watch: bool, config: &Config) -> Result<(), Error> {
+    let mut answered = HashSet::new();
+    loop {
+        scan(&file, &mut answered, config)?;
+        if !watch || interrupted() {
+            return Ok(());
+        }
+        thread::sleep(POLL);
+    }
+}
@@ -0,0 +1,32 @@
+use std::collections::BTreeMap;
+
+#[derive(Debug, Default)]
+pub struct Tree {
+    children: BTreeMap<String, Tree>,
+}
+
+impl Tree {
+    pub fn insert(&mut self, name: &str) {
+        let mut node = self;
+        for part in name.split('/').filter(|part| !part.is_empty()) {
+            node = node.children.entry(part.to_string()).or_default();
+        }
+    }
+
+    pub fn print(&self) {
+        self.print_children("");
+    }
+
+    fn print_children(&self, prefix: &str) {
+        let count = self.children.len();
+        for (i, (name, child)) in self.children.iter().enumerate() {
+            let (branch, indent) = if i + 1 == count {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            println!("{}{}{}", prefix, branch, name);
+            child.print_children(&format!("{}{}", prefix, indent));
+        }
+    }
+}
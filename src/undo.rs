@@ -0,0 +1,31 @@
+use crate::err::Error;
+use crate::file;
+use crate::{quiet, read};
+use log::info;
+use std::path::PathBuf;
+
+pub fn undo(file: PathBuf) -> Result<(), Error> {
+    if file.is_dir() {
+        return Err(Error::UndoDir(file.display().to_string()));
+    }
+
+    let backup = file::with_suffix(&file, ".bak");
+    if !backup.is_file() {
+        return Err(Error::NoBackup(backup.display().to_string()));
+    }
+
+    let current = read(&file)?;
+    let previous = read(&backup)?;
+
+    if current == previous {
+        info!("Password file matches its backup, nothing to undo");
+    } else {
+        if !file::dry_run() && !quiet() {
+            println!("Restoring {}:", backup.display());
+            file::print_changes(&current, &previous);
+        }
+        file::write(&file, &previous)?;
+    }
+
+    Ok(())
+}
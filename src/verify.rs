@@ -0,0 +1,49 @@
+use crate::err::Error;
+use crate::file;
+use crate::split;
+use crate::{quiet, Secret};
+use log::info;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+fn check(file: &Path, update: bool) -> Result<(), Error> {
+    let data = Secret::new(fs::read(file).map_err(Error::PassFile)?);
+    if update {
+        return file::write_checksum(file, &data);
+    }
+
+    let sum = file::with_suffix(file, ".sum");
+    let expected = match fs::read_to_string(&sum) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(Error::NoChecksum(sum.display().to_string()))
+        }
+        Err(e) => return Err(Error::ChecksumRead(e)),
+    };
+
+    if expected.trim() != file::checksum(&data) {
+        return Err(Error::Modified(file.display().to_string()));
+    }
+
+    Ok(())
+}
+
+pub fn verify(file: PathBuf, update: bool) -> Result<(), Error> {
+    let files = if file.is_dir() {
+        split::files(&file)?
+    } else {
+        vec![file.clone()]
+    };
+    for file in &files {
+        check(file, update)?;
+    }
+
+    if update {
+        info!("Recorded the current password file as known-good");
+    } else if !quiet() {
+        println!("{} is unchanged since pw last wrote it", file.display());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use crate::err::Error;
+use crate::Secret;
+use std::env;
+use std::io::{self, Read, Write};
+use std::process;
+use std::thread;
+use std::time::Duration;
+use wl_clipboard_rs::copy::{
+    self, ClipboardType, MimeSource, MimeType, Options, PreparedCopy, Seat, Source,
+};
+
+const READY: &str = "ready";
+const HINT: &str = "x-kde-passwordManagerHint";
+
+fn clipboard(primary: bool) -> ClipboardType {
+    if primary {
+        ClipboardType::Primary
+    } else {
+        ClipboardType::Regular
+    }
+}
+
+fn detach() {
+    unsafe {
+        libc::setsid();
+        let null = libc::open(b"/dev/null\0".as_ptr().cast(), libc::O_RDWR);
+        if null >= 0 {
+            for fd in 0..3 {
+                libc::dup2(null, fd);
+            }
+        }
+    }
+}
+
+pub fn copy(text: &str, primary: bool) -> Result<(), Error> {
+    let exe = env::current_exe().map_err(Error::ClipFork)?;
+    let mut child = process::Command::new(exe)
+        .arg("--serve-clipboard")
+        .arg(if primary { "primary" } else { "clipboard" })
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .stderr(process::Stdio::null())
+        .spawn()
+        .map_err(Error::ClipFork)?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).map_err(Error::ClipWrite)?;
+    }
+
+    let mut reply = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        stdout.read_to_string(&mut reply).map_err(Error::ClipWait)?;
+    }
+    if reply.trim_end() == READY {
+        return Ok(());
+    }
+
+    let _ = child.wait();
+    match reply.trim_end() {
+        "" => Err(Error::Wayland(String::from("the clipboard process failed"))),
+        reply => Err(Error::Wayland(reply.to_string())),
+    }
+}
+
+fn prepare(primary: bool) -> Result<PreparedCopy, String> {
+    let mut text = Secret::new(Vec::new());
+    io::stdin()
+        .read_to_end(&mut text)
+        .map_err(|e| e.to_string())?;
+
+    let mut options = Options::new();
+    options.clipboard(clipboard(primary));
+    options
+        .prepare_copy_multi(vec![
+            MimeSource {
+                source: Source::Bytes(text.as_slice().into()),
+                mime_type: MimeType::Text,
+            },
+            MimeSource {
+                source: Source::Bytes(b"secret"[..].into()),
+                mime_type: MimeType::Specific(String::from(HINT)),
+            },
+        ])
+        .map_err(|e| e.to_string())
+}
+
+pub fn serve(primary: bool, clear_after_secs: u32) {
+    let prepared = match prepare(primary) {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            println!("{}", e);
+            process::exit(1);
+        }
+    };
+    println!("{}", READY);
+    let _ = io::stdout().flush();
+
+    detach();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_secs(clear_after_secs.into()));
+        let _ = copy::clear(clipboard(primary), Seat::All);
+        process::exit(0);
+    });
+    let _ = prepared.serve();
+}
@@ -0,0 +1,63 @@
+use crate::audit;
+use crate::config::Config;
+use crate::err::Error;
+use crate::{find, read, Secret, VALID};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+const QRENCODE: &str = "qrencode";
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ';' | ',' | ':' | '"') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+pub fn wifi_qr(
+    file: PathBuf,
+    acc: String,
+    output: Option<PathBuf>,
+    config: &Config,
+) -> Result<(), Error> {
+    let data = read(file)?;
+    let entry = find(&data, VALID, &acc)?;
+    if !entry.tags().any(|tag| tag == "wifi") {
+        return Err(Error::NotWifi(entry.name.to_string()));
+    }
+    audit::record(config, "wifi-qr", &[entry.name])?;
+
+    let payload = Secret::new(format!(
+        "WIFI:T:WPA;S:{};P:{};;",
+        escape(entry.name),
+        Secret::new(escape(entry.password)).as_str()
+    ));
+
+    let mut command = process::Command::new(QRENCODE);
+    match &output {
+        Some(output) => command.arg("--output").arg(output),
+        None => command.args(["-t", "ANSIUTF8"]),
+    };
+    let mut child = command
+        .stdin(process::Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::QrSpawn(QRENCODE, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(payload.as_bytes())
+            .map_err(|e| Error::QrWrite(QRENCODE, e))?;
+    }
+
+    let exit_status = child.wait().map_err(|e| Error::QrWait(QRENCODE, e))?;
+    if !exit_status.success() {
+        return Err(Error::QrErr(QRENCODE));
+    }
+
+    Ok(())
+}